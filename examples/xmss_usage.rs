@@ -0,0 +1,54 @@
+// Basic XMSS usage example
+//
+// Generates a keypair, signs a few messages and verifies them,
+// both with standard WOTS and with the hypercube (TSL) encoding.
+
+use hypercube_signatures::{XMSSKeypair, XMSSParams, XMSSSignature};
+
+fn main() {
+    // Parameters: (tree_height, winternitz_parameter, len)
+    let params = XMSSParams::new(4, 67, 16);
+    let mut keypair = XMSSKeypair::generate(&params);
+
+    for i in 0..3 {
+        let message = format!("Hello, XMSS! #{}", i);
+        let signature = keypair.sign(message.as_bytes());
+
+        let is_valid =
+            keypair
+                .public_key()
+                .verify(message.as_bytes(), &signature, keypair.params());
+        println!(
+            "Signature {} (leaf {}) valid: {}",
+            i,
+            signature.leaf_index(),
+            is_valid
+        );
+    }
+
+    // Signatures can be serialized and parsed back
+    let message = b"Serialized message";
+    let signature = keypair.sign(message);
+    let bytes = signature.to_bytes();
+    let parsed = XMSSSignature::from_bytes(&bytes, &params).expect("valid signature bytes");
+    println!(
+        "Serialized signature: {} bytes, valid after parsing: {}",
+        bytes.len(),
+        keypair
+            .public_key()
+            .verify(message, &parsed, keypair.params())
+    );
+
+    // Hypercube-optimized XMSS using the TSL encoding
+    let hc_params = XMSSParams::new_with_hypercube(4, 128, true);
+    let mut hc_keypair = XMSSKeypair::generate(&hc_params);
+    let message = b"Hypercube optimized XMSS";
+    let signature = hc_keypair.sign(message);
+    println!(
+        "Hypercube signature with {} WOTS chains valid: {}",
+        signature.wots_signature().chains().len(),
+        hc_keypair
+            .public_key()
+            .verify(message, &signature, hc_keypair.params())
+    );
+}
@@ -5,19 +5,88 @@
 // that are fundamental to the security of the signature schemes.
 
 use crate::core::hypercube::Vertex;
+use crate::core::mapping::MappingError;
+use crate::crypto::random::SecureRandom;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 
 /// Trait for encoding schemes that map messages to hypercube vertices
 /// Paper Section 2: Abstract definition of encoding function f: M × R → [w]^v
 /// where M is the message space and R is the randomness space.
 pub trait EncodingScheme {
     /// Encode a message with randomness to a vertex
+    ///
+    /// # Panics
+    ///
+    /// Implementations whose mapping can fail panic where `try_encode`
+    /// returns an error. For TSL, TL1C and TLFC that is a message longer than
+    /// the configured `max_message_bytes`. Use `try_encode` on caller input.
     fn encode(&self, message: &[u8], randomness: &[u8]) -> Vertex;
 
+    /// Encode a message with randomness, surfacing mapping failures
+    /// Encodings whose mapping can fail override this; the default wraps
+    /// `encode`, which is then infallible.
+    fn try_encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        Ok(self.encode(message, randomness))
+    }
+
     /// Get the alphabet size w
     fn alphabet_size(&self) -> usize;
 
     /// Get the dimension v
     fn dimension(&self) -> usize;
+
+    /// Scheme identifier, e.g. "TSL", for dispatch and diagnostics
+    fn name(&self) -> &str;
+
+    /// Number of bits of H(m || r) that actually feed the mapping Ψ
+    /// If this is below log₂ of the support size, the encoding cannot reach
+    /// every vertex uniformly. The default is for encodings that read the
+    /// first 8 bytes of the digest; TSL, TL1C and TLFC reduce the whole digest.
+    fn entropy_bits_consumed(&self) -> usize {
+        64
+    }
+
+    /// Encode a message to the full WOTS digit vector in [0, w-1]
+    /// The vertex components (a₁, ..., aᵥ) are shifted from [1, w] to [0, w-1];
+    /// schemes with checksum chains append their checksum digits. Fails where
+    /// `try_encode` does.
+    fn encode_to_digest(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Vec<usize>, MappingError> {
+        Ok(self
+            .try_encode(message, randomness)?
+            .components()
+            .iter()
+            .map(|&x| x.saturating_sub(1))
+            .collect())
+    }
+
+    /// Layers the encoding can map to
+    /// The default is every layer of [w]^v, i.e. 0..=v(w-1).
+    fn support_layers(&self) -> RangeInclusive<usize> {
+        0..=self.dimension() * (self.alphabet_size() - 1)
+    }
+
+    /// Configured top layer d₀ and number of checksum chains c
+    /// The default takes d₀ as the deepest supported layer, with no checksum.
+    fn layer_params(&self) -> LayerParams {
+        LayerParams {
+            d0: *self.support_layers().end(),
+            c: 0,
+        }
+    }
+}
+
+/// Layer parameters of an encoding, as configured rather than estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayerParams {
+    /// Top layer bound d₀ (TSL: the single layer used)
+    pub d0: usize,
+    /// Number of checksum chains appended to the v message chains
+    pub c: usize,
 }
 
 /// Trait for non-uniform mapping functions
@@ -31,6 +100,32 @@ pub trait NonUniformMapping {
     /// Get the probability of mapping to a specific vertex
     /// Paper Definition: Pr[Ψ(z) = x] for vertex x ∈ [w]^v
     fn probability(&self, vertex: &Vertex) -> f64;
+
+    /// Fraction of sampled vertex pairs that are incomparable
+    /// Samples `pairs` pairs Ψ(z₁), Ψ(z₂) for uniform 64-bit z and counts those
+    /// where neither vertex dominates the other. Equal vertices count as
+    /// comparable, so collisions lower the fraction as well.
+    fn empirical_incomparability(&self, pairs: usize, rng: &mut dyn SecureRandom) -> f64 {
+        if pairs == 0 {
+            return 0.0;
+        }
+
+        let sample = |rng: &mut dyn SecureRandom| {
+            let bytes = rng.random_bytes(8);
+            let value = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+            self.map(value as usize)
+        };
+
+        let incomparable = (0..pairs)
+            .filter(|_| {
+                let a = sample(rng);
+                let b = sample(rng);
+                a.incomparable(&b)
+            })
+            .count();
+
+        incomparable as f64 / pairs as f64
+    }
 }
 
 /// Calculate the collision metric μ_ℓ²(f)
@@ -56,6 +151,53 @@ pub fn calculate_collision_metric(mapping: &dyn NonUniformMapping, v: usize, w:
     sum
 }
 
+/// Estimate μ_ℓ²(Ψ) by sampling Ψ(z) for `samples` uniform 64-bit z
+/// μ_ℓ² is the probability that two independent outputs collide, so the
+/// estimate is the fraction of colliding pairs Σ cₓ(cₓ - 1) / (n(n - 1))
+/// over the observed counts cₓ. Unlike `calculate_collision_metric` this
+/// does not enumerate the hypercube, so it is usable for any v and w.
+pub fn estimate_collision_metric(
+    mapping: &dyn NonUniformMapping,
+    v: usize,
+    w: usize,
+    samples: usize,
+    rng: &mut dyn SecureRandom,
+) -> f64 {
+    if samples < 2 {
+        return 0.0;
+    }
+
+    let mut counts = HashMap::new();
+    for _ in 0..samples {
+        let bytes = rng.random_bytes(8);
+        let value = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        let vertex = mapping.map(value as usize);
+        debug_assert_eq!(vertex.dimension(), v);
+        debug_assert!(vertex.components().iter().all(|x| (1..=w).contains(x)));
+        *counts.entry(vertex.components().clone()).or_insert(0usize) += 1;
+    }
+
+    let colliding_pairs: usize = counts.values().map(|&c| c * (c - 1)).sum();
+    colliding_pairs as f64 / (samples * (samples - 1)) as f64
+}
+
+/// Exact μ_ℓ²(Ψ) restricted to layer d: Σ_{x ∈ layer d} Pr[Ψ(z) = x]²
+/// Equals the full metric when Ψ is supported on layer d, as for TSL where
+/// it is 1/ℓ_d.
+pub fn collision_metric_layer(
+    mapping: &dyn NonUniformMapping,
+    v: usize,
+    w: usize,
+    d: usize,
+) -> f64 {
+    use crate::core::hypercube::Hypercube;
+
+    Hypercube::new(w, v)
+        .vertices_in_layer(d)
+        .map(|vertex| mapping.probability(&vertex).powi(2))
+        .sum()
+}
+
 // Helper iterator for all vertices
 struct AllVertices {
     w: usize,
@@ -133,6 +275,10 @@ mod tests {
                 self.w
             }
 
+            fn name(&self) -> &str {
+                "Test"
+            }
+
             fn dimension(&self) -> usize {
                 self.v
             }
@@ -141,6 +287,9 @@ mod tests {
         let encoding = TestEncoding { w: 4, v: 3 };
         let vertex = encoding.encode(b"test", b"rand");
         assert_eq!(vertex.components(), &vec![4, 4, 4]);
+
+        // Without an override the support is the whole hypercube
+        assert_eq!(encoding.support_layers(), 0..=9);
     }
 
     #[test]
@@ -243,6 +392,36 @@ mod tests {
         assert!((metric - 0.25).abs() < 0.001);
     }
 
+    #[test]
+    fn test_collision_metric_layer_matches_tsl() {
+        use crate::schemes::tsl::{TSLConfig, TSL};
+
+        for (w, v, d) in [(4, 4, 6), (3, 3, 3), (5, 3, 7)] {
+            let tsl = TSL::new(TSLConfig::with_params(w, v, d));
+            let layer_size = calculate_layer_size(d, v, w).unwrap().to_usize().unwrap();
+
+            let metric = collision_metric_layer(&tsl, v, w, d);
+            assert!((metric - 1.0 / layer_size as f64).abs() < 1e-12);
+
+            // Other layers carry no probability mass under TSL
+            assert_eq!(collision_metric_layer(&tsl, v, w, d - 1), 0.0);
+        }
+    }
+
+    #[test]
+    fn test_estimate_collision_metric() {
+        use crate::crypto::random::DeterministicRng;
+        use crate::schemes::tsl::{TSLConfig, TSL};
+
+        // [4]^4 layer 6 holds 44 vertices, so μ² = 1/44 ≈ 0.0227
+        let tsl = TSL::new(TSLConfig::with_params(4, 4, 6));
+        let mut rng = DeterministicRng::new(b"collision metric");
+        let estimate = estimate_collision_metric(&tsl, 4, 4, 4000, &mut rng);
+
+        let exact = collision_metric_layer(&tsl, 4, 4, 6);
+        assert!((estimate - exact).abs() < 0.005, "estimate {}", estimate);
+    }
+
     #[test]
     fn test_target_collision_resistance() {
         // Test that encoding provides target collision resistance
@@ -276,6 +455,10 @@ mod tests {
                 self.w
             }
 
+            fn name(&self) -> &str {
+                "HashBased"
+            }
+
             fn dimension(&self) -> usize {
                 self.v
             }
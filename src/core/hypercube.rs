@@ -4,6 +4,10 @@
 // This module implements the fundamental hypercube structure [w]^v
 // and associated operations as defined in the paper.
 
+use crate::core::mapping::{calculate_layer_size, integer_to_vertex_big};
+use num_bigint::BigUint;
+use num_traits::Zero;
+
 /// Represents a hypercube [w]^v
 /// Paper Definition (Section 2.1): The hypercube [w]^v consists of all
 /// v-dimensional integer vectors with components from [w] = {1, 2, ..., w}
@@ -64,16 +68,20 @@ impl Hypercube {
     }
 
     /// Returns an iterator over all vertices in a given layer
+    /// Walks the indices 0..ℓ_d through the MapToVertex bijection, so each
+    /// vertex is yielded exactly once, in index order.
     pub fn vertices_in_layer(&self, layer: usize) -> impl Iterator<Item = Vertex> {
-        // For now, we'll use a simple but inefficient approach
-        // In production, we'd use the mapping functions
         let w = self.w;
         let v = self.v;
-
-        AllVertices::new(w, v).filter(move |vertex| {
-            let sum: usize = vertex.components.iter().sum();
-            v * w - sum == layer
-        })
+        let layer_size = calculate_layer_size(layer, v, w).unwrap_or_else(|_| BigUint::zero());
+
+        std::iter::successors(Some(BigUint::zero()), |i| Some(i + 1u32))
+            .take_while(move |i| i < &layer_size)
+            .map(move |i| {
+                let components =
+                    integer_to_vertex_big(&i, w, v, layer).expect("index is below the layer size");
+                Vertex::new(components)
+            })
     }
 
     /// Calculates the distance from a vertex to the sink
@@ -82,6 +90,16 @@ impl Hypercube {
     pub fn distance_from_sink(&self, vertex: &Vertex) -> usize {
         vertex.components.iter().map(|&x| self.w - x).sum()
     }
+
+    /// Checks that the given vertices are pairwise incomparable
+    /// An encoding whose codewords form an antichain cannot have one signature
+    /// advanced to another by hashing forward. Repeated vertices are comparable.
+    pub fn is_antichain(&self, vertices: &[Vertex]) -> bool {
+        vertices
+            .iter()
+            .enumerate()
+            .all(|(i, a)| vertices[i + 1..].iter().all(|b| a.incomparable(b)))
+    }
 }
 
 impl Vertex {
@@ -111,55 +129,22 @@ impl Vertex {
                 .zip(other.components.iter())
                 .all(|(&x, &y)| x <= y)
     }
-}
 
-/// Iterator over all vertices in a hypercube
-struct AllVertices {
-    w: usize,
-    v: usize,
-    current: Vec<usize>,
-    finished: bool,
-}
-
-impl AllVertices {
-    fn new(w: usize, v: usize) -> Self {
-        AllVertices {
-            w,
-            v,
-            current: vec![1; v],
-            finished: false,
-        }
+    /// Checks if this vertex is greater than or equal to another (component-wise)
+    /// A signature on a dominated vertex can be advanced to this one by
+    /// hashing forward, so encodings avoid comparable supports.
+    pub fn dominates(&self, other: &Self) -> bool {
+        other.le(self)
     }
-}
 
-impl Iterator for AllVertices {
-    type Item = Vertex;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
-            return None;
-        }
-
-        let result = Vertex::new(self.current.clone());
-
-        // Increment to next vertex
-        let mut carry = true;
-        for i in (0..self.v).rev() {
-            if carry {
-                if self.current[i] < self.w {
-                    self.current[i] += 1;
-                    carry = false;
-                } else {
-                    self.current[i] = 1;
-                }
-            }
-        }
-
-        if carry {
-            self.finished = true;
-        }
+    /// Checks if either vertex is less than or equal to the other
+    pub fn is_comparable(&self, other: &Self) -> bool {
+        self.le(other) || other.le(self)
+    }
 
-        Some(result)
+    /// Checks if neither vertex is less than or equal to the other
+    pub fn incomparable(&self, other: &Self) -> bool {
+        !self.is_comparable(other)
     }
 }
 
@@ -224,6 +209,42 @@ mod tests {
         assert!(!v3.le(&v1));
     }
 
+    #[test]
+    fn test_vertex_comparability() {
+        let a = Vertex::new(vec![1, 2, 2]);
+        let b = Vertex::new(vec![2, 1, 2]);
+        assert!(a.incomparable(&b));
+        assert!(!a.is_comparable(&b));
+
+        let c = Vertex::new(vec![1, 2, 3]);
+        let d = Vertex::new(vec![2, 3, 4]);
+        assert!(c.is_comparable(&d));
+        assert!(d.is_comparable(&c));
+        assert!(!c.incomparable(&d));
+
+        // A vertex is comparable to itself
+        assert!(a.is_comparable(&a));
+    }
+
+    #[test]
+    fn test_is_antichain() {
+        let hc = Hypercube::new(4, 3);
+
+        // Any single layer is an antichain
+        let layer: Vec<Vertex> = hc.vertices_in_layer(3).collect();
+        assert!(hc.is_antichain(&layer));
+        assert!(hc.is_antichain(&[]));
+
+        let mixed = vec![Vertex::new(vec![1, 2, 2]), Vertex::new(vec![2, 1, 2])];
+        assert!(hc.is_antichain(&mixed));
+
+        let chain = vec![Vertex::new(vec![1, 2, 3]), Vertex::new(vec![2, 3, 4])];
+        assert!(!hc.is_antichain(&chain));
+
+        let repeated = vec![Vertex::new(vec![1, 2, 2]), Vertex::new(vec![1, 2, 2])];
+        assert!(!hc.is_antichain(&repeated));
+    }
+
     #[test]
     fn test_layer_membership() {
         let hc = Hypercube::new(4, 3);
@@ -255,6 +276,28 @@ mod tests {
         // Layer 1 should have 3 vertices
         let layer_1_vertices: Vec<_> = hc.vertices_in_layer(1).collect();
         assert_eq!(layer_1_vertices.len(), 3);
+        for expected in [[1, 2, 2], [2, 1, 2], [2, 2, 1]] {
+            assert!(layer_1_vertices.contains(&Vertex::new(expected.to_vec())));
+        }
+    }
+
+    #[test]
+    fn test_vertices_in_layer_matches_layer_size() {
+        use num_traits::ToPrimitive;
+
+        let hc = Hypercube::new(4, 4);
+        for layer in 0..=12 {
+            let vertices: Vec<_> = hc.vertices_in_layer(layer).collect();
+            let expected = calculate_layer_size(layer, 4, 4).unwrap();
+            assert_eq!(vertices.len(), expected.to_usize().unwrap());
+
+            let distinct: std::collections::HashSet<_> =
+                vertices.iter().map(|x| x.components().clone()).collect();
+            assert_eq!(distinct.len(), vertices.len());
+            assert!(vertices.iter().all(|x| hc.calculate_layer(x) == layer));
+        }
+
+        assert_eq!(hc.vertices_in_layer(13).count(), 0);
     }
 
     #[test]
@@ -0,0 +1,69 @@
+// Layer-size sanity checks
+// Based on "At the Top of the Hypercube" Section 2
+//
+// The layer-size formula is an inclusion-exclusion sum with alternating
+// signs, so a wrong term or a clamped underflow silently produces bad sizes.
+// These checks validate a parameter set against two identities before the
+// sizes are trusted.
+
+use crate::core::mapping::{calculate_layer_size, MappingError};
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// Check the layer sizes of [w]^v against the hypercube identities
+/// Σ_{d=0}^{v(w-1)} ℓ_d = w^v, and ℓ_d = ℓ_{v(w-1)-d} (vertex a ↦ (w+1) - a
+/// maps layer d onto layer v(w-1) - d).
+pub fn self_test(w: usize, v: usize) -> bool {
+    self_test_with(w, v, calculate_layer_size)
+}
+
+/// Same as `self_test`, for an arbitrary layer-size function (d, v, w) ↦ ℓ_d
+pub fn self_test_with<F>(w: usize, v: usize, layer_size: F) -> bool
+where
+    F: Fn(usize, usize, usize) -> Result<BigUint, MappingError>,
+{
+    if w < 2 {
+        return false;
+    }
+
+    let max_layer = v * (w - 1);
+    let mut sizes = Vec::with_capacity(max_layer + 1);
+    for d in 0..=max_layer {
+        match layer_size(d, v, w) {
+            Ok(size) if !size.is_zero() => sizes.push(size),
+            _ => return false,
+        }
+    }
+
+    let total: BigUint = sizes.iter().sum();
+    if total != BigUint::from(w).pow(v as u32) {
+        return false;
+    }
+
+    (0..=max_layer).all(|d| sizes[d] == sizes[max_layer - d])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_self_test() {
+        assert!(self_test(4, 4));
+        assert!(self_test(6, 10));
+
+        // A stub that drops the inclusion-exclusion correction overcounts
+        // the layers near the middle
+        let broken = |d: usize, v: usize, w: usize| {
+            if d == v * (w - 1) / 2 {
+                Ok(calculate_layer_size(d, v, w)? + 1u32)
+            } else {
+                calculate_layer_size(d, v, w)
+            }
+        };
+        assert!(!self_test_with(4, 4, broken));
+
+        // A stub that returns 0 (e.g. after overflow) is rejected
+        assert!(!self_test_with(4, 4, |_, _, _| Ok(BigUint::zero())));
+    }
+}
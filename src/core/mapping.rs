@@ -4,8 +4,9 @@
 // This module implements the bijective mappings between vertices in a layer
 // and integers [0, ℓ_d), as well as the non-uniform mapping function Ψ.
 
+use crate::core::hypercube::Vertex;
 use num_bigint::BigUint;
-use num_traits::{CheckedSub, One, ToPrimitive, Zero};
+use num_traits::{One, ToPrimitive, Zero};
 
 /// Maps a vertex in layer d to an integer in [0, ℓ_d)
 /// Paper Section 4.3: Bijective mapping from layer d vertices to {0, 1, ..., ℓ_d - 1}
@@ -20,6 +21,20 @@ pub fn vertex_to_integer(
     v: usize,
     d: usize,
 ) -> Result<usize, MappingError> {
+    vertex_to_integer_big(vertex, w, v, d)?
+        .to_usize()
+        .ok_or(MappingError::IntegerOverflow)
+}
+
+/// Maps a vertex in layer d to its index in [0, ℓ_d), for layers of any size
+/// Inverse of `integer_to_vertex_big`; `vertex_to_integer` is this with the
+/// result narrowed to usize.
+pub fn vertex_to_integer_big(
+    vertex: &[usize],
+    w: usize,
+    v: usize,
+    d: usize,
+) -> Result<BigUint, MappingError> {
     // Verify input vertex is valid
     if vertex.len() != v {
         return Err(MappingError::InvalidLayer {
@@ -79,14 +94,13 @@ pub fn vertex_to_integer(
         });
     }
 
-    // Convert to usize and return
-    x_v.to_usize().ok_or(MappingError::IntegerOverflow)
+    Ok(x_v)
 }
 
 /// Calculate layer size using the exact formula from the paper
 /// ℓ_d = Σ_{s=0}^{⌊d/w⌋} (-1)^s · C(v,s) · C(d-s·w+v-1, v-1)
-/// This is the same formula as in layer.rs but returns BigUint for exact arithmetic
-/// in the mapping calculations.
+/// Returns BigUint for exact arithmetic in the mapping calculations; see
+/// `layer::self_test` for a runtime check of the resulting sizes.
 pub fn calculate_layer_size(d: usize, v: usize, w: usize) -> Result<BigUint, MappingError> {
     if v == 0 {
         return Ok(if d == 0 {
@@ -100,7 +114,10 @@ pub fn calculate_layer_size(d: usize, v: usize, w: usize) -> Result<BigUint, Map
         return Ok(BigUint::zero());
     }
 
-    let mut sum = BigUint::zero();
+    // Accumulate the positive and negative terms separately: partial sums of
+    // the alternating series can go negative, which BigUint cannot represent
+    let mut positive = BigUint::zero();
+    let mut negative = BigUint::zero();
     let max_s = d / w;
 
     for s in 0..=max_s {
@@ -120,13 +137,14 @@ pub fn calculate_layer_size(d: usize, v: usize, w: usize) -> Result<BigUint, Map
         // Apply inclusion-exclusion principle
         let term = binom_v_s * binom_inner;
         if s % 2 == 0 {
-            sum += term;
+            positive += term;
         } else {
-            sum = sum.checked_sub(&term).unwrap_or(BigUint::zero());
+            negative += term;
         }
     }
 
-    Ok(sum)
+    // The full sum counts vertices, so it is never negative
+    Ok(positive - negative)
 }
 
 /// Calculate exact binomial coefficient C(n, k) using the paper's requirements
@@ -155,7 +173,7 @@ fn binomial_coefficient(n: usize, k: usize) -> BigUint {
 // Helper function removed - no longer needed in the current implementation
 
 /// Error types for mapping operations
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum MappingError {
     InvalidLayer {
         expected: usize,
@@ -167,10 +185,24 @@ pub enum MappingError {
         max: usize,
     },
     IntegerOverflow,
+    /// Vertex has the wrong number of coordinates
+    InvalidDimension {
+        expected: usize,
+        actual: usize,
+    },
+    /// Layer d of the hypercube has no vertices
+    EmptyLayer {
+        d: usize,
+    },
     IndexOutOfRange {
         index: usize,
         max: usize,
     },
+    /// Message longer than the configured `max_message_bytes`
+    MessageTooLong {
+        len: usize,
+        max: usize,
+    },
 }
 
 /// Maps an integer in [0, ℓ_d) to a vertex in layer d
@@ -185,112 +217,116 @@ pub fn integer_to_vertex(
     v: usize,
     d: usize,
 ) -> Result<Vec<usize>, MappingError> {
-    let layer_size_big = calculate_layer_size(d, v, w)?;
+    integer_to_vertex_big(&BigUint::from(x), w, v, d)
+}
 
-    // For very large layer sizes, accept any reasonable x value
-    if let Some(layer_size) = layer_size_big.to_usize() {
-        if x >= layer_size {
-            return Err(MappingError::IndexOutOfRange {
-                index: x,
-                max: layer_size,
-            });
-        }
+/// Maps an integer in [0, ℓ_d) to a vertex in layer d, for layers of any size
+/// Same MapToVertex algorithm as `integer_to_vertex`, with all index
+/// arithmetic in BigUint so that layers larger than usize are covered exactly.
+pub fn integer_to_vertex_big(
+    x: &BigUint,
+    w: usize,
+    v: usize,
+    d: usize,
+) -> Result<Vec<usize>, MappingError> {
+    let layer_size = calculate_layer_size(d, v, w)?;
+
+    let out_of_range = || MappingError::IndexOutOfRange {
+        index: x.to_usize().unwrap_or(usize::MAX),
+        max: layer_size.to_usize().unwrap_or(usize::MAX),
+    };
+
+    if v == 0 || *x >= layer_size {
+        return Err(out_of_range());
     }
-    // If layer_size is too large to fit in usize, we proceed with the algorithm
-    // assuming x is within a reasonable range
 
-    let mut vertex = vec![0; v];
-    let mut x_i = x;
+    let mut vertex = Vec::with_capacity(v);
+    let mut x_i = x.clone();
     let mut d_i = d;
 
     // Process each coordinate position from 1 to v-1
     for i in 0..v - 1 {
-        let remaining_dims = v - i;
+        let remaining_dims = v - i - 1;
 
-        // Calculate the valid range for j_i
-        let j_min = if d_i > (w - 1) * (remaining_dims - 1) {
-            d_i - (w - 1) * (remaining_dims - 1)
-        } else {
-            0
-        };
+        // j_i must leave a remaining layer reachable by the other coordinates
+        let j_min = d_i.saturating_sub((w - 1) * remaining_dims);
         let j_max = d_i.min(w - 1);
 
-        // Find j_i such that the sum condition is satisfied
-        let mut sum_before = BigUint::zero();
-        let mut j_i = j_min;
-
+        // Find j_i such that x_i falls in the block of vertices with a_i = w - j_i,
+        // subtracting the sizes of the blocks before it
+        let mut j_i = None;
         for j in j_min..=j_max {
-            let sub_d = d_i - j;
-            let sub_v = remaining_dims - 1;
-            let layer_size = calculate_layer_size(sub_d, sub_v, w)?;
-
-            let sum_including = &sum_before + &layer_size;
-
-            if let Some(sum_including_usize) = sum_including.to_usize() {
-                if x_i < sum_including_usize {
-                    j_i = j;
-                    break;
-                }
-            } else {
-                // If sum_including is too large, x_i is definitely smaller
-                j_i = j;
+            let block_size = calculate_layer_size(d_i - j, remaining_dims, w)?;
+            if x_i < block_size {
+                j_i = Some(j);
                 break;
             }
-
-            sum_before = sum_including;
-
-            if j == j_max {
-                // x_i is beyond valid range, use actual layer size if available
-                let max_size = layer_size_big.to_usize().unwrap_or(usize::MAX);
-                return Err(MappingError::IndexOutOfRange {
-                    index: x,
-                    max: max_size,
-                });
-            }
+            x_i -= block_size;
         }
 
-        // Set a_i := w - j_i
-        vertex[i] = w - j_i;
+        // The blocks partition layer d_i, so x_i < ℓ_{d_i} always lands in one
+        let j_i = j_i.ok_or_else(out_of_range)?;
 
-        // Update d_{i+1} and x_{i+1}
-        d_i = d_i - j_i;
-
-        // Calculate the sum to subtract from x_i
-        let mut sum_to_subtract = BigUint::zero();
-        for j in j_min..j_i {
-            let sub_d = d_i + j_i - j;
-            let sub_v = remaining_dims - 1;
-            sum_to_subtract += calculate_layer_size(sub_d, sub_v, w)?;
-        }
-
-        if let Some(sum_to_subtract_usize) = sum_to_subtract.to_usize() {
-            x_i = x_i.saturating_sub(sum_to_subtract_usize);
-        } else {
-            // If sum_to_subtract is very large, set x_i to 0
-            x_i = 0;
-        }
+        // Set a_i := w - j_i and update d_{i+1}
+        vertex.push(w - j_i);
+        d_i -= j_i;
     }
 
-    // Set a_v := w - x_v - d_v
-    if x_i + d_i > w {
-        let max_size = layer_size_big.to_usize().unwrap_or(usize::MAX);
-        return Err(MappingError::IndexOutOfRange {
-            index: x,
-            max: max_size,
-        });
-    }
-    vertex[v - 1] = w - x_i - d_i;
+    // The last coordinate is fixed by the remaining layer: a_v := w - d_v
+    vertex.push(w - d_i);
 
-    // Verify the result is in the correct layer
-    let actual_layer = v * w - vertex.iter().sum::<usize>();
-    if actual_layer != d {
-        return Err(MappingError::InvalidLayer {
-            expected: d,
-            actual: actual_layer,
-        });
+    Ok(vertex)
+}
+
+/// Panic unless MapToVertex and MapToInteger are inverse bijections on layer d
+/// Checks every index in [0, ℓ_d): the vertex has valid coordinates, lies in
+/// layer d and maps back to the index, and the ℓ_d images are distinct.
+/// Meant for property tests; the layer must be small enough to enumerate.
+pub fn assert_bijective(w: usize, v: usize, d: usize) {
+    let layer_size = calculate_layer_size(d, v, w)
+        .unwrap()
+        .to_usize()
+        .expect("layer too large to enumerate");
+    let mut image = std::collections::HashSet::with_capacity(layer_size);
+
+    for i in 0..layer_size {
+        let vertex = integer_to_vertex(i, w, v, d)
+            .unwrap_or_else(|e| panic!("[{}]^{} layer {}: index {} failed: {:?}", w, v, d, i, e));
+
+        assert_eq!(vertex.len(), v, "index {} gave {:?}", i, vertex);
+        assert!(
+            vertex.iter().all(|x| (1..=w).contains(x)),
+            "index {} gave out-of-range {:?}",
+            i,
+            vertex
+        );
+        assert_eq!(
+            v * w - vertex.iter().sum::<usize>(),
+            d,
+            "index {} gave {:?} outside layer {}",
+            i,
+            vertex,
+            d
+        );
+        assert_eq!(
+            vertex_to_integer(&vertex, w, v, d),
+            Ok(i),
+            "{:?} does not map back to {}",
+            vertex,
+            i
+        );
+
+        image.insert(vertex);
     }
 
-    Ok(vertex)
+    assert_eq!(
+        image.len(),
+        layer_size,
+        "[{}]^{} layer {} has repeated vertices",
+        w,
+        v,
+        d
+    );
 }
 
 /// Non-uniform mapping function Ψ as defined in the paper
@@ -383,6 +419,115 @@ impl MapToInteger {
     }
 }
 
+/// Bijection between the top layers [0, d₀] of [w]^v and [0, ℓ_{[0:d₀]})
+/// Paper Section 2.2: TL1C and TLFC map uniformly onto the union of layers
+/// [0, d₀]. Layer d occupies the indices [Σ_{j<d} ℓ_j, Σ_{j≤d} ℓ_j), and
+/// within a layer vertices follow the MapToVertex / MapToInteger order.
+#[derive(Debug, Clone)]
+pub struct TopLayersMapper {
+    w: usize,
+    v: usize,
+    /// ℓ_d for d in [0, d0]
+    layer_sizes: Vec<BigUint>,
+    /// Cumulative sizes: `layer_ends[d]` = Σ_{j ≤ d} ℓ_j
+    layer_ends: Vec<BigUint>,
+}
+
+impl TopLayersMapper {
+    /// Precompute the layer sizes and prefix sums of layers [0, d0]
+    pub fn new(w: usize, v: usize, d0: usize) -> Result<Self, MappingError> {
+        let mut total = BigUint::zero();
+        let mut layer_sizes = Vec::with_capacity(d0 + 1);
+        let mut layer_ends = Vec::with_capacity(d0 + 1);
+        for d in 0..=d0 {
+            let layer_size = calculate_layer_size(d, v, w)?;
+            total += &layer_size;
+            layer_sizes.push(layer_size);
+            layer_ends.push(total.clone());
+        }
+
+        Ok(TopLayersMapper {
+            w,
+            v,
+            layer_sizes,
+            layer_ends,
+        })
+    }
+
+    /// Top layer d₀ of the mapped range
+    pub fn d0(&self) -> usize {
+        self.layer_ends.len() - 1
+    }
+
+    /// ℓ_{[0:d₀]} = Σ_{d ≤ d₀} ℓ_d, the number of mapped vertices
+    pub fn total_size(&self) -> &BigUint {
+        // new always pushes the d = 0 entry
+        &self.layer_ends[self.d0()]
+    }
+
+    /// Map a global index in [0, ℓ_{[0:d₀]}) to a vertex in layers [0, d₀]
+    pub fn to_vertex(&self, index: usize) -> Result<Vertex, MappingError> {
+        self.to_vertex_big(&BigUint::from(index))
+    }
+
+    /// Map a global index of any size to a vertex in layers [0, d₀]
+    pub fn to_vertex_big(&self, index: &BigUint) -> Result<Vertex, MappingError> {
+        // Binary search for the first layer whose cumulative end exceeds the index
+        let d = self.layer_ends.partition_point(|end| end <= index);
+        if d == self.layer_ends.len() {
+            return Err(MappingError::IndexOutOfRange {
+                index: index.to_usize().unwrap_or(usize::MAX),
+                max: self.total_size().to_usize().unwrap_or(usize::MAX),
+            });
+        }
+
+        let layer_start = &self.layer_ends[d] - &self.layer_sizes[d];
+        integer_to_vertex_big(&(index - layer_start), self.w, self.v, d).map(Vertex::new)
+    }
+
+    /// Map a vertex in layers [0, d₀] to its global index, narrowed to usize
+    pub fn to_integer(&self, vertex: &Vertex) -> Result<usize, MappingError> {
+        self.to_integer_big(vertex)?
+            .to_usize()
+            .ok_or(MappingError::IntegerOverflow)
+    }
+
+    /// Map a vertex in layers [0, d₀] to its global index in [0, ℓ_{[0:d₀]})
+    /// Inverse of `to_vertex_big`.
+    pub fn to_integer_big(&self, vertex: &Vertex) -> Result<BigUint, MappingError> {
+        let d0 = self.d0();
+        let vertex = vertex.components();
+        if vertex.len() != self.v {
+            return Err(MappingError::InvalidDimension {
+                expected: self.v,
+                actual: vertex.len(),
+            });
+        }
+
+        // Coordinates are checked before the layer so the sum cannot exceed vw
+        for (i, &coord) in vertex.iter().enumerate() {
+            if coord < 1 || coord > self.w {
+                return Err(MappingError::InvalidCoordinate {
+                    position: i,
+                    value: coord,
+                    max: self.w,
+                });
+            }
+        }
+
+        let d = self.v * self.w - vertex.iter().sum::<usize>();
+        if d > d0 {
+            return Err(MappingError::InvalidLayer {
+                expected: d0,
+                actual: d,
+            });
+        }
+
+        let layer_start = &self.layer_ends[d] - &self.layer_sizes[d];
+        Ok(layer_start + vertex_to_integer_big(vertex, self.w, self.v, d)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -562,6 +707,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_assert_bijective() {
+        for (w, v, d) in [(2, 3, 1), (3, 3, 3), (4, 4, 6), (5, 3, 7), (6, 2, 4)] {
+            assert_bijective(w, v, d);
+        }
+
+        // The sink and source layers hold a single vertex each
+        for (w, v) in [(2, 3), (4, 4), (5, 3)] {
+            assert_bijective(w, v, 0);
+            assert_bijective(w, v, v * (w - 1));
+        }
+    }
+
     #[test]
     fn test_edge_cases() {
         // Test edge cases for the new implementation
@@ -583,4 +741,95 @@ mod tests {
         // Test invalid indices
         assert!(integer_to_vertex(layer_size, w, v, d,).is_err());
     }
+
+    #[test]
+    fn test_big_mapping_round_trip() {
+        // Paper TSL parameters for 128 bits: ℓ_{d₀} is far beyond 2^64
+        let (w, v, d) = (86, 25, 384);
+        let layer_size = calculate_layer_size(d, v, w).unwrap();
+        assert!(layer_size.bits() > 64);
+
+        let samples = [
+            BigUint::zero(),
+            BigUint::from(u64::MAX) + 1u32,
+            &layer_size / 3u32,
+            &layer_size / 2u32,
+            &layer_size - 1u32,
+        ];
+
+        let mut vertices = Vec::new();
+        for x in &samples {
+            let vertex = integer_to_vertex_big(x, w, v, d).unwrap();
+            assert_eq!(vertex.len(), v);
+            assert_eq!(v * w - vertex.iter().sum::<usize>(), d);
+            assert_eq!(&vertex_to_integer_big(&vertex, w, v, d).unwrap(), x);
+            vertices.push(vertex);
+        }
+
+        vertices.dedup();
+        assert_eq!(vertices.len(), samples.len());
+
+        assert!(integer_to_vertex_big(&layer_size, w, v, d).is_err());
+        assert_eq!(
+            vertex_to_integer(&vertices[4], w, v, d),
+            Err(MappingError::IntegerOverflow)
+        );
+    }
+
+    #[test]
+    fn test_top_layers_mapper_round_trip() {
+        for &(w, v, d0) in &[(2, 3, 3), (4, 4, 3), (5, 3, 12), (6, 4, 9)] {
+            let mapper = TopLayersMapper::new(w, v, d0).unwrap();
+            assert_eq!(mapper.d0(), d0);
+
+            let expected: BigUint = (0..=d0)
+                .map(|d| calculate_layer_size(d, v, w).unwrap())
+                .sum();
+            assert_eq!(mapper.total_size(), &expected);
+
+            let total = mapper.total_size().to_usize().unwrap();
+            let mut seen = std::collections::HashSet::new();
+            let mut previous_layer = 0;
+            for index in 0..total {
+                let vertex = mapper.to_vertex(index).unwrap();
+                assert_eq!(vertex.dimension(), v);
+                assert!(vertex.components().iter().all(|&a| (1..=w).contains(&a)));
+
+                // Indices run through the layers in increasing order
+                let layer = v * w - vertex.components().iter().sum::<usize>();
+                assert!(layer <= d0 && layer >= previous_layer);
+                previous_layer = layer;
+
+                assert_eq!(mapper.to_integer(&vertex).unwrap(), index);
+                assert!(seen.insert(vertex.components().clone()));
+            }
+
+            assert_eq!(
+                mapper.to_vertex(total),
+                Err(MappingError::IndexOutOfRange {
+                    index: total,
+                    max: total
+                })
+            );
+        }
+
+        // Vertices below layer d0 are outside the mapped range
+        let mapper = TopLayersMapper::new(4, 4, 3).unwrap();
+        assert_eq!(
+            mapper.to_integer(&Vertex::new(vec![1, 1, 1, 1])),
+            Err(MappingError::InvalidLayer {
+                expected: 3,
+                actual: 12
+            })
+        );
+
+        // A vertex of the wrong dimension is rejected as such
+        assert_eq!(
+            mapper.to_integer(&Vertex::new(vec![4, 4, 4])),
+            Err(MappingError::InvalidDimension {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
 }
@@ -1,3 +1,4 @@
 pub mod encoding;
 pub mod hypercube;
+pub mod layer;
 pub mod mapping;
@@ -1,17 +1,81 @@
 // Hash function abstractions
 
-use sha2::{Digest, Sha256};
-use sha3::Sha3_256;
+use sha2::{Digest, Sha256, Sha512};
+use sha3::digest::{ExtendableOutput, XofReader};
+use sha3::{Sha3_256, Shake256};
 
 /// Trait for hash functions
 pub trait HashFunction {
+    /// Start an incremental hash computation
+    fn hasher(&self) -> Box<dyn IncrementalHasher>;
+
     /// Hash input data
-    fn hash(&self, data: &[u8]) -> Vec<u8>;
+    /// Implementations override this with a one-shot hash, since the default
+    /// boxes an incremental state per call.
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        let mut hasher = self.hasher();
+        hasher.update(data);
+        hasher.finalize()
+    }
 
     /// Get output size in bytes
     fn output_size(&self) -> usize;
 }
 
+/// Running state of a hash computation
+/// Feeding the input in pieces gives the same digest as hashing it at once.
+pub trait IncrementalHasher {
+    /// Absorb more input
+    fn update(&mut self, data: &[u8]);
+
+    /// Absorb everything `reader` yields, a fixed-size buffer at a time
+    fn update_reader(&mut self, reader: &mut dyn std::io::Read) -> std::io::Result<()> {
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(n) => self.update(&buffer[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Consume the state and return the digest
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+}
+
+/// Incremental state for the fixed-output `Digest` hashes
+struct DigestHasher<D: Digest>(D);
+
+impl<D: Digest> IncrementalHasher for DigestHasher<D> {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+}
+
+/// Incremental state for SHAKE256, squeezed to a fixed length
+struct ShakeHasher {
+    state: Shake256,
+    output_size: usize,
+}
+
+impl IncrementalHasher for ShakeHasher {
+    fn update(&mut self, data: &[u8]) {
+        sha3::digest::Update::update(&mut self.state, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        let mut output = vec![0u8; self.output_size];
+        self.state.finalize_xof().read(&mut output);
+        output
+    }
+}
+
 /// SHA-256 hash function
 pub struct SHA256;
 
@@ -22,10 +86,12 @@ impl SHA256 {
 }
 
 impl HashFunction for SHA256 {
+    fn hasher(&self) -> Box<dyn IncrementalHasher> {
+        Box::new(DigestHasher(Sha256::new()))
+    }
+
     fn hash(&self, data: &[u8]) -> Vec<u8> {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        hasher.finalize().to_vec()
+        Sha256::digest(data).to_vec()
     }
 
     fn output_size(&self) -> usize {
@@ -43,13 +109,186 @@ impl SHA3_256 {
 }
 
 impl HashFunction for SHA3_256 {
+    fn hasher(&self) -> Box<dyn IncrementalHasher> {
+        Box::new(DigestHasher(Sha3_256::new()))
+    }
+
     fn hash(&self, data: &[u8]) -> Vec<u8> {
-        let mut hasher = Sha3_256::new();
-        hasher.update(data);
-        hasher.finalize().to_vec()
+        Sha3_256::digest(data).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+}
+
+/// SHA-512 hash function
+#[derive(Default)]
+pub struct SHA512;
+
+impl SHA512 {
+    pub fn new() -> Self {
+        SHA512
+    }
+}
+
+impl HashFunction for SHA512 {
+    fn hasher(&self) -> Box<dyn IncrementalHasher> {
+        Box::new(DigestHasher(Sha512::new()))
+    }
+
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        Sha512::digest(data).to_vec()
+    }
+
+    fn output_size(&self) -> usize {
+        64
+    }
+}
+
+/// SHAKE256 with a 32-byte output
+#[derive(Default)]
+pub struct SHAKE256;
+
+impl SHAKE256 {
+    pub fn new() -> Self {
+        SHAKE256
+    }
+}
+
+impl HashFunction for SHAKE256 {
+    fn hasher(&self) -> Box<dyn IncrementalHasher> {
+        Box::new(ShakeHasher {
+            state: Shake256::default(),
+            output_size: self.output_size(),
+        })
+    }
+
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        let mut state = Shake256::default();
+        sha3::digest::Update::update(&mut state, data);
+        let mut output = vec![0u8; self.output_size()];
+        state.finalize_xof().read(&mut output);
+        output
+    }
+
+    fn output_size(&self) -> usize {
+        32
+    }
+}
+
+/// BLAKE3 with its default 32-byte output
+/// Much faster than SHA-256 in software, which matters for the many short
+/// inputs hashed along WOTS chains.
+#[derive(Default)]
+pub struct Blake3;
+
+impl Blake3 {
+    pub fn new() -> Self {
+        Blake3
+    }
+}
+
+/// Incremental state for BLAKE3
+struct Blake3Hasher(blake3::Hasher);
+
+impl IncrementalHasher for Blake3Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().as_bytes().to_vec()
+    }
+}
+
+impl HashFunction for Blake3 {
+    fn hasher(&self) -> Box<dyn IncrementalHasher> {
+        Box::new(Blake3Hasher(blake3::Hasher::new()))
+    }
+
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        blake3::hash(data).as_bytes().to_vec()
     }
 
     fn output_size(&self) -> usize {
         32
     }
 }
+
+/// Runtime selector for the hash used by WOTS chains and the encoders
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HashAlg {
+    #[default]
+    Sha256,
+    Sha512,
+    Shake256,
+    Blake3,
+}
+
+impl HashFunction for HashAlg {
+    fn hasher(&self) -> Box<dyn IncrementalHasher> {
+        match self {
+            HashAlg::Sha256 => SHA256.hasher(),
+            HashAlg::Sha512 => SHA512.hasher(),
+            HashAlg::Shake256 => SHAKE256.hasher(),
+            HashAlg::Blake3 => Blake3.hasher(),
+        }
+    }
+
+    fn hash(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlg::Sha256 => SHA256.hash(data),
+            HashAlg::Sha512 => SHA512.hash(data),
+            HashAlg::Shake256 => SHAKE256.hash(data),
+            HashAlg::Blake3 => Blake3.hash(data),
+        }
+    }
+
+    fn output_size(&self) -> usize {
+        match self {
+            HashAlg::Sha256 => SHA256.output_size(),
+            HashAlg::Sha512 => SHA512.output_size(),
+            HashAlg::Shake256 => SHAKE256.output_size(),
+            HashAlg::Blake3 => Blake3.output_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incremental_matches_one_shot() {
+        let data = b"incremental hashing test input";
+        let algorithms: [&dyn HashFunction; 5] = [&SHA256, &SHA3_256, &SHA512, &SHAKE256, &Blake3];
+
+        for hash in algorithms {
+            let mut hasher = hash.hasher();
+            for chunk in data.chunks(7) {
+                hasher.update(chunk);
+            }
+            let digest = hasher.finalize();
+
+            assert_eq!(digest, hash.hash(data));
+            assert_eq!(digest.len(), hash.output_size());
+        }
+
+        // Known SHA-256 vector for "abc"
+        assert_eq!(SHA256.hash(b"abc")[..4], [0xba, 0x78, 0x16, 0xbf]);
+    }
+
+    #[test]
+    fn test_blake3() {
+        let hash = Blake3::new();
+
+        assert_eq!(hash.hash(b"abc"), hash.hash(b"abc"));
+        assert_eq!(hash.hash(b"abc").len(), 32);
+        assert_eq!(HashAlg::Blake3.output_size(), 32);
+        assert_ne!(hash.hash(b"abc"), SHA256.hash(b"abc"));
+
+        // Known BLAKE3 vector for "abc"
+        assert_eq!(hash.hash(b"abc")[..4], [0x64, 0x37, 0xb3, 0xac]);
+    }
+}
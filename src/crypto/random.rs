@@ -1,7 +1,8 @@
 // Random number generation
 
 use rand::rngs::OsRng;
-use rand::RngCore;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
 
 /// Secure random number generator trait
 pub trait SecureRandom {
@@ -28,6 +29,29 @@ impl SecureRandom for OsSecureRandom {
     }
 }
 
+/// Seedable ChaCha20 generator
+/// The same seed always yields the same byte stream, for reproducible keys
+/// in test vectors and deterministic protocols.
+pub struct ChaChaRandom {
+    rng: ChaCha20Rng,
+}
+
+impl ChaChaRandom {
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        ChaChaRandom {
+            rng: ChaCha20Rng::from_seed(seed),
+        }
+    }
+}
+
+impl SecureRandom for ChaChaRandom {
+    fn random_bytes(&mut self, size: usize) -> Vec<u8> {
+        let mut bytes = vec![0u8; size];
+        self.rng.fill_bytes(&mut bytes);
+        bytes
+    }
+}
+
 /// Deterministic RNG for testing
 pub struct DeterministicRng {
     seed: Vec<u8>,
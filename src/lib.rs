@@ -1,11 +1,14 @@
 pub mod core;
 pub mod crypto;
 pub mod schemes;
+pub mod signer;
 pub mod wots;
 pub mod xmss;
 
+pub use signer::{Signer, Verifier};
+
 // Re-export main XMSS types for convenient external usage
 pub use xmss::{
     AuthPath, MerkleTree, WOTSPlusParams, XMSSKeypair, XMSSParams, XMSSPrivateKey, XMSSPublicKey,
-    XMSSSignature,
+    XMSSSignature, XMSSSignatureError, XmssError,
 };
@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+/// Reasons a scheme parameter set fails validation
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParamError {
+    #[error("layer d0 = {d0} exceeds the top layer v(w-1) = {max}")]
+    LayerOutOfRange { d0: usize, max: usize },
+
+    #[error("layer d0 holds 2^{bits} vertices, below the required 2^{required}")]
+    LayerTooSmall { bits: usize, required: usize },
+
+    #[error("hypercube [w]^v holds 2^{bits} vertices, below the required 2^{required}")]
+    HypercubeTooSmall { bits: usize, required: usize },
+
+    #[error("checksum chain {chain} has weight 2^{chain}, a multiple of w = {w}")]
+    ChecksumWeightVanishes { chain: usize, w: usize },
+
+    #[error("{c} checksum chains over w = {w} cannot encode checksums up to {max}")]
+    ChecksumTooSmall { c: usize, w: usize, max: usize },
+}
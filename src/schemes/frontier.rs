@@ -0,0 +1,187 @@
+// Signature-size / verification-cost tradeoff
+//
+// Paper Section 5 compares the schemes by signature size against verifier
+// hashing. This module enumerates parameter sets meeting a security level and
+// keeps the Pareto-optimal ones.
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// Bytes per chain value (SHA-256 output)
+const CHAIN_BYTES: usize = 32;
+
+/// Alphabet sizes considered, covering the paper's parameter tables
+const W_CANDIDATES: [usize; 19] = [
+    2, 3, 4, 5, 6, 7, 8, 9, 10, 13, 14, 18, 20, 21, 26, 28, 44, 56, 86,
+];
+
+/// TLFC checksum chain counts considered
+const C_CANDIDATES: [usize; 3] = [1, 2, 4];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchemeKind {
+    Tsl,
+    Tl1c,
+    Tlfc,
+}
+
+/// A parameter set with its cost
+/// `verify_hash_ops` is the worst-case number of chain hashes the verifier computes:
+/// - TSL: exactly d₀ (Σ(w - aᵢ) = d₀ for every signed vertex)
+/// - TL1C: w - 1 (vertex chains cost d, the checksum chain w - 1 - d)
+/// - TLFC: at most d₀ + c(w - 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrontierPoint {
+    pub scheme: SchemeKind,
+    pub w: usize,
+    pub v: usize,
+    pub d0: usize,
+    /// Checksum chains (0 for TSL, 1 for TL1C)
+    pub c: usize,
+    pub signature_bytes: usize,
+    pub verify_hash_ops: usize,
+}
+
+impl FrontierPoint {
+    /// Whether `self` is at least as good as `other` in both costs and better in one
+    pub fn dominates(&self, other: &FrontierPoint) -> bool {
+        self.signature_bytes <= other.signature_bytes
+            && self.verify_hash_ops <= other.verify_hash_ops
+            && (self.signature_bytes < other.signature_bytes
+                || self.verify_hash_ops < other.verify_hash_ops)
+    }
+}
+
+/// Non-dominated (signature_bytes, verify_hash_ops) points across all three schemes
+/// For each w in the candidate set and v ≤ 2λ, d₀ is the smallest value whose
+/// support reaches 2^λ vertices (ℓ_{d₀} for TSL, Σ_{d ≤ d₀} ℓ_d otherwise).
+/// Points are sorted by increasing signature size.
+pub fn pareto_frontier(security_bits: usize) -> Vec<FrontierPoint> {
+    let target = BigUint::one() << security_bits;
+    let max_v = 2 * security_bits;
+    let mut points = Vec::new();
+
+    for &w in &W_CANDIDATES {
+        // Layer sizes of [w]^v: coefficients of (1 + x + ... + x^{w-1})^v
+        let mut layers = vec![BigUint::one()];
+
+        for v in 1..=max_v {
+            layers = next_layer_sizes(&layers, w);
+            let middle = v * (w - 1) / 2;
+
+            // TSL: layer sizes grow up to the middle layer
+            if let Some(d0) = (0..=middle).find(|&d| layers[d] >= target) {
+                points.push(FrontierPoint {
+                    scheme: SchemeKind::Tsl,
+                    w,
+                    v,
+                    d0,
+                    c: 0,
+                    signature_bytes: v * CHAIN_BYTES,
+                    verify_hash_ops: d0,
+                });
+            }
+
+            let mut cumulative = BigUint::zero();
+            let top_layers_d0 = layers.iter().position(|size| {
+                cumulative += size;
+                cumulative >= target
+            });
+
+            if let Some(d0) = top_layers_d0 {
+                // TL1C: the checksum d₀ + 1 must fit in the alphabet
+                if d0 < w {
+                    points.push(FrontierPoint {
+                        scheme: SchemeKind::Tl1c,
+                        w,
+                        v,
+                        d0,
+                        c: 1,
+                        signature_bytes: (v + 1) * CHAIN_BYTES,
+                        verify_hash_ops: w - 1,
+                    });
+                }
+
+                for &c in &C_CANDIDATES {
+                    points.push(FrontierPoint {
+                        scheme: SchemeKind::Tlfc,
+                        w,
+                        v,
+                        d0,
+                        c,
+                        signature_bytes: (v + c) * CHAIN_BYTES,
+                        verify_hash_ops: d0 + c * (w - 1),
+                    });
+                }
+            }
+        }
+    }
+
+    // Sweep by size: keep a point only if it strictly improves verification cost
+    points.sort_by_key(|p| (p.signature_bytes, p.verify_hash_ops));
+    let mut frontier: Vec<FrontierPoint> = Vec::new();
+    for point in points {
+        let improves = match frontier.last() {
+            Some(last) => point.verify_hash_ops < last.verify_hash_ops,
+            None => true,
+        };
+        if improves {
+            frontier.push(point);
+        }
+    }
+
+    frontier
+}
+
+/// Layer sizes of [w]^{v+1} from those of [w]^v: ℓ'_d = Σ_{j=0}^{w-1} ℓ_{d-j}
+fn next_layer_sizes(layers: &[BigUint], w: usize) -> Vec<BigUint> {
+    let len = layers.len() + w - 1;
+    let mut next = Vec::with_capacity(len);
+    let mut window = BigUint::zero();
+
+    for d in 0..len {
+        if d < layers.len() {
+            window += &layers[d];
+        }
+        if d >= w {
+            window -= &layers[d - w];
+        }
+        next.push(window.clone());
+    }
+
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::mapping::calculate_layer_size;
+
+    #[test]
+    fn test_next_layer_sizes_matches_formula() {
+        let mut layers = vec![BigUint::one()];
+        for _ in 0..5 {
+            layers = next_layer_sizes(&layers, 4);
+        }
+
+        for (d, size) in layers.iter().enumerate() {
+            assert_eq!(size, &calculate_layer_size(d, 5, 4).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_pareto_frontier_128() {
+        let frontier = pareto_frontier(128);
+        assert!(!frontier.is_empty());
+
+        for p in &frontier {
+            assert!(!frontier.iter().any(|q| q.dominates(p)));
+        }
+
+        // Size increases while verification cost decreases along the frontier
+        for pair in frontier.windows(2) {
+            assert!(pair[0].signature_bytes < pair[1].signature_bytes);
+            assert!(pair[0].verify_hash_ops > pair[1].verify_hash_ops);
+        }
+    }
+}
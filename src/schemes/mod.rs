@@ -1,3 +1,250 @@
+pub mod error;
+pub mod frontier;
 pub mod tl1c;
 pub mod tlfc;
 pub mod tsl;
+
+pub use self::error::ParamError;
+pub use self::frontier::{pareto_frontier, FrontierPoint, SchemeKind};
+
+use crate::core::hypercube::{Hypercube, Vertex};
+use crate::core::mapping::calculate_layer_size;
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// Common interface of the scheme configurations
+pub trait SchemeConfig {
+    /// Estimated security level in bits: ⌊log₂⌋ of the encoding's support size
+    /// The paper requires the support to hold at least 2^λ vertices for λ-bit
+    /// security, so a small value flags test-only parameters.
+    fn security_bits_estimate(&self) -> usize;
+}
+
+/// Integer fed to the layer mapping: the whole digest read as little-endian
+/// Shared by TSL, TL1C and TLFC so signer and verifier agree on the byte order.
+pub fn digest_to_integer(digest: &[u8]) -> BigUint {
+    BigUint::from_bytes_le(digest)
+}
+
+/// ⌊log₂ Σ_{d ∈ layers} ℓ_d⌋ for layers of [w]^v
+fn support_bits(w: usize, v: usize, layers: std::ops::RangeInclusive<usize>) -> usize {
+    let support: BigUint = layers
+        .map(|d| calculate_layer_size(d, v, w).unwrap_or_else(|_| BigUint::zero()))
+        .sum();
+
+    support.bits().saturating_sub(1) as usize
+}
+
+/// Which schemes' support and checksum constraints a digest satisfies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemeClasses {
+    /// TSL: the vertex lies exactly in layer d₀
+    pub tsl: bool,
+    /// TL1C: the vertex lies in layers [0, d₀] and the checksum is C = d + 1
+    pub tl1c: bool,
+}
+
+/// Classify a digest (a₁, ..., aᵥ, C) against the TSL and TL1C constraints
+/// for the same hypercube [w]^v and top layer d₀.
+/// Used by verifiers accepting either scheme (e.g. during a scheme migration)
+/// to tell whether a digest could be valid under both.
+/// The first v digits form the vertex; the optional trailing digit is the
+/// TL1C checksum. All digits are in hypercube range [1, w].
+pub fn classify_digest(digest: &[usize], w: usize, v: usize, d0: usize) -> SchemeClasses {
+    let invalid = SchemeClasses {
+        tsl: false,
+        tl1c: false,
+    };
+
+    if digest.len() < v || digest.iter().any(|x| !(1..=w).contains(x)) {
+        return invalid;
+    }
+
+    let vertex = Vertex::new(digest[..v].to_vec());
+    let layer = Hypercube::new(w, v).calculate_layer(&vertex);
+
+    // Paper Construction 4: TSL support is the single layer d₀
+    let tsl = layer == d0;
+
+    // Paper Construction 3: TL1C support is [0, d₀] with checksum C = d + 1
+    let tl1c = digest.len() == v + 1 && layer <= d0 && digest[v] == layer + 1;
+
+    SchemeClasses { tsl, tl1c }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::encoding::EncodingScheme;
+    use crate::wots::{WotsKeypair, WotsParams};
+
+    #[test]
+    fn test_digest_to_integer() {
+        use crate::crypto::hash::{HashFunction, SHA256};
+
+        assert_eq!(digest_to_integer(&[0x01, 0x02]), BigUint::from(0x0201u32));
+        assert!(digest_to_integer(&[]).is_zero());
+
+        // SHA-256("abc") starts ba 78 16 bf 8f 01 cf ea
+        let value = digest_to_integer(&SHA256::new().hash(b"abc"));
+        assert_eq!(value.to_u64_digits()[0], 0xeacf_018f_bf16_78ba);
+        assert_eq!(value.bits(), 256);
+    }
+
+    #[test]
+    fn test_classify_digest() {
+        let (w, v, d0) = (5, 3, 3);
+
+        // (5, 4, 3) is in layer 3 = d0, checksum 4 = d0 + 1
+        let both = classify_digest(&[5, 4, 3, 4], w, v, d0);
+        assert!(both.tsl);
+        assert!(both.tl1c);
+
+        // (5, 5, 4) is in layer 1 < d0, checksum 2 = layer + 1
+        let lower = classify_digest(&[5, 5, 4, 2], w, v, d0);
+        assert!(!lower.tsl);
+        assert!(lower.tl1c);
+
+        // Wrong checksum for layer d0 only satisfies TSL
+        let bad_checksum = classify_digest(&[5, 4, 3, 2], w, v, d0);
+        assert!(bad_checksum.tsl);
+        assert!(!bad_checksum.tl1c);
+
+        // Out-of-range digits satisfy neither
+        let invalid = classify_digest(&[0, 4, 3, 4], w, v, d0);
+        assert!(!invalid.tsl);
+        assert!(!invalid.tl1c);
+    }
+
+    #[test]
+    fn test_encode_to_digest_matches_signature_chains() {
+        let (message, randomness) = (b"test message".as_slice(), b"random seed".as_slice());
+
+        let tsl_config = tsl::TSLConfig::with_params(5, 3, 3);
+        let tsl_chains = tsl_config.signature_chains();
+        let tsl = tsl::TSL::new(tsl_config);
+        assert_eq!(
+            tsl.encode_to_digest(message, randomness).unwrap().len(),
+            tsl_chains
+        );
+
+        let tl1c_config = tl1c::TL1CConfig::with_params(5, 3, 3);
+        let tl1c_chains = tl1c_config.signature_chains();
+        let tl1c = tl1c::TL1C::new(tl1c_config);
+        assert_eq!(
+            tl1c.encode_to_digest(message, randomness).unwrap().len(),
+            tl1c_chains
+        );
+
+        let tlfc_config = tlfc::TLFCConfig::with_params(5, 3, 3, 2);
+        let tlfc_chains = tlfc_config.signature_chains();
+        let tlfc = tlfc::TLFC::new(tlfc_config);
+        let digest = tlfc.encode_to_digest(message, randomness).unwrap();
+        assert_eq!(digest.len(), tlfc_chains);
+        assert!(digest.iter().all(|&x| x < 5));
+
+        // WotsKeypair::sign and verify_message go through the same digest
+        let keypair = WotsKeypair::generate(&WotsParams::new(5, tl1c_chains));
+        let signature = keypair.sign(message, &tl1c).unwrap();
+        assert!(keypair
+            .public_key()
+            .verify_message(message, &tl1c, &signature));
+        assert!(!keypair
+            .public_key()
+            .verify_message(b"other message", &tl1c, &signature));
+    }
+
+    #[test]
+    fn test_verify_message_rejects_over_long_message() {
+        use crate::core::mapping::MappingError;
+        use crate::wots::WotsError;
+
+        let long = [7u8; 1000];
+        let too_long = WotsError::Encoding(MappingError::MessageTooLong { len: 1000, max: 16 });
+
+        let tsl = tsl::TSL::new(tsl::TSLConfig::with_params(5, 3, 3).with_max_message_bytes(16));
+        let tl1c =
+            tl1c::TL1C::new(tl1c::TL1CConfig::with_params(5, 3, 3).with_max_message_bytes(16));
+        let tlfc =
+            tlfc::TLFC::new(tlfc::TLFCConfig::with_params(5, 3, 3, 2).with_max_message_bytes(16));
+
+        fn check<E: EncodingScheme>(encoding: &E, chains: usize, long: &[u8], err: &WotsError) {
+            let keypair = WotsKeypair::generate(&WotsParams::new(5, chains));
+            assert_eq!(keypair.sign(long, encoding).unwrap_err(), *err);
+            assert!(encoding.encode_to_digest(long, &[0u8; 32]).is_err());
+
+            // Any signature is rejected without panicking
+            let signature = keypair.sign(b"short message", encoding).unwrap();
+            let public_key = keypair.public_key();
+            assert!(public_key.verify_message(b"short message", encoding, &signature));
+            assert!(!public_key.verify_message(long, encoding, &signature));
+        }
+
+        check(&tsl, 3, &long, &too_long);
+        check(&tl1c, 4, &long, &too_long);
+        check(&tlfc, 5, &long, &too_long);
+    }
+
+    #[test]
+    fn test_layer_params_match_configs() {
+        use crate::core::encoding::LayerParams;
+
+        let tsl_config = tsl::TSLConfig::new(128);
+        let d0 = tsl_config.d0();
+        assert_eq!(
+            tsl::TSL::new(tsl_config).layer_params(),
+            LayerParams { d0, c: 0 }
+        );
+
+        let tl1c = tl1c::TL1C::new(tl1c::TL1CConfig::with_params(5, 3, 3));
+        assert_eq!(tl1c.layer_params(), LayerParams { d0: 3, c: 1 });
+
+        let tlfc = tlfc::TLFC::new(tlfc::TLFCConfig::with_params(5, 3, 3, 2));
+        assert_eq!(tlfc.layer_params(), LayerParams { d0: 3, c: 2 });
+
+        // The checksum count is the number of chains beyond the v message chains
+        let digest = tlfc.encode_to_digest(b"message", b"randomness").unwrap();
+        assert_eq!(digest.len(), tlfc.dimension() + tlfc.layer_params().c);
+    }
+
+    #[test]
+    fn test_scheme_names() {
+        let tsl = tsl::TSL::new(tsl::TSLConfig::with_params(5, 3, 3));
+        let tl1c = tl1c::TL1C::new(tl1c::TL1CConfig::with_params(5, 3, 3));
+        let tlfc = tlfc::TLFC::new(tlfc::TLFCConfig::with_params(5, 3, 3, 2));
+
+        assert_eq!(tsl.name(), "TSL");
+        assert_eq!(tl1c.name(), "TL1C");
+        assert_eq!(tlfc.name(), "TLFC");
+    }
+
+    #[test]
+    fn test_security_bits_estimate() {
+        // Test-sized parameters offer only a few bits of security
+        let toy = tsl::TSLConfig::with_params(4, 4, 4);
+        assert!(toy.security_bits_estimate() < 16);
+        assert!(tl1c::TL1CConfig::with_params(4, 4, 3).security_bits_estimate() < 16);
+        assert!(tlfc::TLFCConfig::with_params(8, 4, 3, 2).security_bits_estimate() < 16);
+
+        // Paper parameters reach the requested level
+        assert!(tsl::TSLConfig::new(128).security_bits_estimate() >= 128);
+    }
+
+    #[test]
+    fn test_empirical_incomparability() {
+        use crate::core::encoding::NonUniformMapping;
+        use crate::crypto::random::DeterministicRng;
+
+        // Same hypercube [4]^4: TSL on the middle layer, TL1C on layers [0, 3]
+        let tsl = tsl::TSL::new(tsl::TSLConfig::with_params(4, 4, 6));
+        let tl1c = tl1c::TL1C::new(tl1c::TL1CConfig::with_params(4, 4, 3));
+
+        let mut rng = DeterministicRng::new(b"incomparability");
+        let tsl_fraction = tsl.empirical_incomparability(500, &mut rng);
+        let tl1c_fraction = tl1c.empirical_incomparability(500, &mut rng);
+
+        // Distinct vertices of one layer are always incomparable
+        assert!(tsl_fraction > 0.9);
+        assert!(tsl_fraction > tl1c_fraction);
+    }
+}
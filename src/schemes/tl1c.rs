@@ -3,11 +3,15 @@
 /// Paper Construction 3:Top Layers with a 1-Chain Checksum
 // TL1C maps messages to multiple layers [0, d₀] with a single checksum chain.
 // This provides better verification efficiency than TSL at the cost of one extra chain.
-use crate::core::encoding::{EncodingScheme, NonUniformMapping};
+use crate::core::encoding::{EncodingScheme, LayerParams, NonUniformMapping};
 use crate::core::hypercube::{Hypercube, Vertex};
-use crate::core::mapping::{calculate_layer_size, integer_to_vertex};
-use crate::crypto::hash::{HashFunction, SHA256};
-use num_traits::ToPrimitive;
+use crate::core::mapping::{calculate_layer_size, MappingError, TopLayersMapper};
+use crate::crypto::hash::{HashAlg, HashFunction};
+use crate::schemes::{digest_to_integer, support_bits, SchemeConfig};
+use crate::wots::{WotsError, WotsKeypair, WotsPublicKey, WotsSignature};
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use std::ops::RangeInclusive;
 
 /// TL1C configuration parameters
 #[derive(Debug, Clone)]
@@ -15,49 +19,43 @@ pub struct TL1CConfig {
     w: usize,
     v: usize,
     d0: usize,
+    max_message_bytes: Option<usize>,
+    hash: HashAlg,
 }
 
-// TODO: Update to use `num_bigint` for large integers if needed
-// This allows us to handle larger values without overflow issues
-// For now, we use usize for simplicity and performance
 impl TL1CConfig {
     /// Create TL1C config for given security level
+    /// Layer sizes are summed as BigUint, so supports beyond usize qualify.
     pub fn new(security_bits: usize) -> Self {
         // For TL1C, we need ℓ_{[0:d₀]} ≥ 2^λ
         // where ℓ_{[0:d₀]} = Σ_{d=0}^{d₀} ℓ_d
         // Try different parameter combinations
         // Note: w must be large enough to accommodate checksum d0+1
         let candidates = vec![
-            (16, 16), // w=16, v=16
-            (32, 12), // w=32, v=12
-            (64, 8),  // w=64, v=8
+            (16, 16),  // w=16, v=16
+            (32, 12),  // w=32, v=12
+            (64, 8),   // w=64, v=8
+            (256, 28), // w=256, v=28: ℓ_{[0:255]} ≈ 2^128
+            (256, 39), // w=256, v=39: ℓ_{[0:255]} ≈ 2^162
         ];
 
         for (w, v) in candidates {
             // Find appropriate d0 such that sum of layer sizes ≥ 2^λ
-            for d0 in 1..=(v * (w - 1)) {
-                // Try to calculate total size with overflow protection
-                let mut total_size_option = Some(0usize);
-                for d in 0..=d0 {
-                    if let Some(current_total) = total_size_option {
-                        let layer_size_big = calculate_layer_size(d, v, w).unwrap();
-                        if let Some(layer_size) = layer_size_big.to_usize() {
-                            total_size_option = current_total.checked_add(layer_size);
-                        } else {
-                            total_size_option = None;
-                            break;
-                        }
-                    }
-                }
-
-                if let Some(total_size) = total_size_option {
-                    if total_size > 0 && (total_size as f64).log2() >= security_bits as f64 {
-                        // Paper: Checksum C = d + 1 must satisfy C ∈ [w]
-                        // So we need d₀ + 1 ≤ w
-                        if d0 + 1 <= w {
-                            return TL1CConfig { w, v, d0 };
-                        }
-                    }
+            // Paper: Checksum C = d + 1 must satisfy C ∈ [w], so d₀ + 1 ≤ w
+            let mut total_size = BigUint::zero();
+            total_size += calculate_layer_size(0, v, w).unwrap();
+            for d0 in 1..=(v * (w - 1)).min(w - 1) {
+                total_size += calculate_layer_size(d0, v, w).unwrap();
+
+                // ⌊log₂ total⌋ ≥ λ
+                if total_size.bits() > security_bits as u64 {
+                    return TL1CConfig {
+                        w,
+                        v,
+                        d0,
+                        max_message_bytes: None,
+                        hash: HashAlg::default(),
+                    };
                 }
             }
         }
@@ -67,17 +65,59 @@ impl TL1CConfig {
             w: 16,
             v: 32,
             d0: 10,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
         }
     }
 
     /// Create TL1C config with specific parameters
+    /// Only available in tests or with the `test-params` feature, since most
+    /// small parameter sets are insecure. Production code must use `new` or
+    /// the explicitly named `with_params_unchecked`.
+    #[cfg(any(test, feature = "test-params"))]
     pub fn with_params(w: usize, v: usize, d0: usize) -> Self {
+        Self::with_params_unchecked(w, v, d0)
+    }
+
+    /// Create TL1C config with specific parameters, without a security check
+    /// Check `SchemeConfig::security_bits_estimate` before using the result.
+    pub fn with_params_unchecked(w: usize, v: usize, d0: usize) -> Self {
         assert!(w > 1, "w must be greater than 1");
         assert!(v > 0, "v must be positive");
         assert!(d0 <= v * (w - 1), "d0 must be valid layer");
         assert!(d0 + 1 <= w, "Checksum d0+1 must fit in alphabet [1,w]");
 
-        TL1CConfig { w, v, d0 }
+        TL1CConfig {
+            w,
+            v,
+            d0,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
+        }
+    }
+
+    /// Reject messages longer than `limit` bytes in `encode`
+    /// Bounds the input a verifier buffers; larger messages must be hashed by
+    /// the caller and encoded with `encode_prehashed`.
+    pub fn with_max_message_bytes(mut self, limit: usize) -> Self {
+        self.max_message_bytes = Some(limit);
+        self
+    }
+
+    pub fn max_message_bytes(&self) -> Option<usize> {
+        self.max_message_bytes
+    }
+
+    /// Select the hash used to derive the vertex index (SHA-256 by default)
+    /// Encodings under different hashes are unrelated, so signer and
+    /// verifier must agree on it.
+    pub fn with_hash(mut self, hash: HashAlg) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    pub fn hash(&self) -> HashAlg {
+        self.hash
     }
 
     pub fn w(&self) -> usize {
@@ -92,48 +132,70 @@ impl TL1CConfig {
         self.d0
     }
 
+    /// Whether every layer in [0, d0] is non-empty
+    /// The cumulative search in `try_map_to_top_layers` assumes the support
+    /// is this contiguous range of layers.
+    pub fn nonempty_layers(&self) -> bool {
+        self.first_empty_layer().is_none()
+    }
+
+    fn first_empty_layer(&self) -> Option<usize> {
+        (0..=self.d0)
+            .find(|&d| calculate_layer_size(d, self.v, self.w).map_or(true, |size| size.is_zero()))
+    }
+
     pub fn signature_chains(&self) -> usize {
         self.v + 1 // TL1C has 1 checksum chain
     }
 }
 
+impl SchemeConfig for TL1CConfig {
+    fn security_bits_estimate(&self) -> usize {
+        support_bits(self.w, self.v, 0..=self.d0)
+    }
+}
+
 /// TL1C encoding scheme
 pub struct TL1C {
     config: TL1CConfig,
-    hasher: SHA256,
-    total_layer_size: usize,
+    hasher: HashAlg,
+    /// Bijection between layers [0, d0] and [0, ℓ_{[0:d₀]})
+    mapper: TopLayersMapper,
 }
 
 impl TL1C {
     pub fn new(config: TL1CConfig) -> Self {
-        // Calculate total size of layers [0, d0] with overflow protection
-        let mut total_layer_size = 0usize;
-        for d in 0..=config.d0 {
-            let layer_size_big = calculate_layer_size(d, config.v, config.w).unwrap();
-            let layer_size = layer_size_big
-                .to_usize()
-                .expect("Layer size too large to fit in usize");
-            total_layer_size = total_layer_size
-                .checked_add(layer_size)
-                .expect("Total layer size overflow");
+        Self::try_new(config).unwrap_or_else(|e| panic!("Invalid TL1C parameters: {:?}", e))
+    }
+
+    /// Fallible constructor
+    /// Rejects a support [0, d0] containing an empty layer.
+    pub fn try_new(config: TL1CConfig) -> Result<Self, MappingError> {
+        if let Some(d) = config.first_empty_layer() {
+            return Err(MappingError::EmptyLayer { d });
         }
 
-        assert!(total_layer_size > 0, "Total layer size must be positive");
+        // Calculate total size of layers [0, d0]
+        let mapper = TopLayersMapper::new(config.w, config.v, config.d0)?;
 
-        TL1C {
+        Ok(TL1C {
+            hasher: config.hash,
             config,
-            hasher: SHA256::new(),
-            total_layer_size,
-        }
+            mapper,
+        })
     }
 
     /// Encode message with 1-chain checksum
-    pub fn encode_with_checksum(&self, message: &[u8], randomness: &[u8]) -> (Vertex, usize) {
-        let vertex = self.encode(message, randomness);
+    pub fn encode_with_checksum(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Vertex, usize), MappingError> {
+        let vertex = self.encode(message, randomness)?;
         let hc = Hypercube::new(self.config.w, self.config.v);
         let layer = hc.calculate_layer(&vertex);
         let checksum = self.calculate_checksum(layer);
-        (vertex, checksum)
+        Ok((vertex, checksum))
     }
 
     /// Calculate checksum for a layer
@@ -144,47 +206,138 @@ impl TL1C {
         layer + 1
     }
 
-    /// Map to top layers [0, d0]
+    /// Map to top layers [0, d0], propagating mapping failures
     /// Paper Section 2.2: Uniform mapping to the union of layers [0, d₀]
     /// Each vertex in this set has probability 1/ℓ_{[0:d₀]}
-    pub fn map_to_top_layers(&self, value: usize) -> Vertex {
+    pub fn try_map_to_top_layers(&self, value: usize) -> Result<Vertex, MappingError> {
+        self.try_map_to_top_layers_big(&BigUint::from(value))
+    }
+
+    /// Map an integer of any size to top layers [0, d0]
+    pub fn try_map_to_top_layers_big(&self, value: &BigUint) -> Result<Vertex, MappingError> {
         // Map uniformly to layers [0, d0]
-        let index = value % self.total_layer_size;
-
-        // Find which layer this index falls into
-        let mut cumulative = 0;
-        for d in 0..=self.config.d0 {
-            let layer_size = calculate_layer_size(d, self.config.v, self.config.w)
-                .unwrap()
-                .to_usize()
-                .unwrap();
-            if index < cumulative + layer_size {
-                // Index is in layer d
-                let layer_index = index - cumulative;
-                let components = integer_to_vertex(layer_index, self.config.w, self.config.v, d)
-                    .unwrap_or_else(|_| vec![self.config.w; self.config.v]);
-                return Vertex::new(components);
-            }
-            cumulative += layer_size;
-        }
+        let index = value % self.mapper.total_size();
+
+        self.mapper.to_vertex_big(&index)
+    }
 
-        // Should not reach here
-        panic!("Index out of range");
+    /// Map to top layers [0, d0]
+    /// Cannot fail: `try_new` builds the mapper for this config, and the value
+    /// is reduced into its support before mapping.
+    pub fn map_to_top_layers(&self, value: usize) -> Vertex {
+        self.try_map_to_top_layers(value)
+            .expect("index reduced into the support always maps to a vertex")
     }
 
     /// Convert message to WOTS digest including checksum
     /// Paper Section 2.2: The WOTS message is (a₁, ..., aᵥ, C)
     /// where (a₁, ..., aᵥ) is the encoded vertex and C = d + 1 is the checksum.
-    pub fn message_to_wots_digest(&self, message: &[u8], randomness: &[u8]) -> Vec<usize> {
-        let (vertex, checksum) = self.encode_with_checksum(message, randomness);
+    pub fn message_to_wots_digest(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Vec<usize>, MappingError> {
+        let (vertex, checksum) = self.encode_with_checksum(message, randomness)?;
 
         let mut digest = vertex.components().clone();
         digest.push(checksum);
 
-        digest
+        Ok(digest)
     }
 
-    fn encode(&self, message: &[u8], randomness: &[u8]) -> Vertex {
+    /// Verify a received WOTS digest (a₁, ..., aᵥ, C)
+    /// Paper Section 2.2: The encoding only produces vertices in layers [0, d₀]
+    /// with checksum C = d + 1, so a digest whose vertex lies in a layer d > d₀
+    /// or whose checksum disagrees with the layer cannot come from an honest signer.
+    pub fn verify_digest(&self, digest: &[usize]) -> bool {
+        let w = self.config.w;
+        let v = self.config.v;
+
+        if digest.len() != self.config.signature_chains() {
+            return false;
+        }
+
+        if digest.iter().any(|x| !(1..=w).contains(x)) {
+            return false;
+        }
+
+        // Recompute the layer d = vw - Σaᵢ from the first v digits
+        let vertex = Vertex::new(digest[..v].to_vec());
+        let layer = Hypercube::new(w, v).calculate_layer(&vertex);
+
+        layer <= self.config.d0 && digest[v] == self.calculate_checksum(layer)
+    }
+
+    /// Sign a message with a WOTS key of v + 1 chains
+    /// The digest (a₁, ..., aᵥ, C) is kept alongside the chains so that its
+    /// internal consistency can be checked without the message.
+    /// Fails if the message cannot be encoded, e.g. when it exceeds
+    /// `max_message_bytes`, or if `keypair` does not have v + 1 chains over [w].
+    pub fn sign(
+        &self,
+        keypair: &WotsKeypair,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Tl1cSignature, WotsError> {
+        let digest = self
+            .message_to_wots_digest(message, randomness)
+            .map_err(WotsError::Encoding)?;
+        let signature = keypair.try_sign_raw(&to_wots_digits(&digest))?;
+
+        Ok(Tl1cSignature {
+            w: self.config.w,
+            digest,
+            signature,
+        })
+    }
+
+    /// Verify a signature against the message, randomness and public key
+    /// A message that cannot be encoded has no valid signature.
+    pub fn verify(
+        &self,
+        public_key: &WotsPublicKey,
+        message: &[u8],
+        randomness: &[u8],
+        signature: &Tl1cSignature,
+    ) -> bool {
+        match self.message_to_wots_digest(message, randomness) {
+            Ok(digest) if digest == signature.digest => {}
+            _ => return false,
+        }
+
+        self.verify_digest(&signature.digest)
+            && public_key.verify(&to_wots_digits(&signature.digest), &signature.signature)
+    }
+
+    /// Encode message and randomness to a vertex in layers [0, d0]
+    pub fn encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        self.check_message_len(message)?;
+        self.encode_unchecked(message, randomness)
+    }
+
+    /// Encode a caller-computed 32-byte message hash, for messages of any length
+    /// Not subject to `max_message_bytes`. Signer and verifier must both use
+    /// the prehashed form, since it encodes H(m) in place of m.
+    pub fn encode_prehashed(
+        &self,
+        message_hash: &[u8; 32],
+        randomness: &[u8],
+    ) -> Result<Vertex, MappingError> {
+        self.encode_unchecked(message_hash, randomness)
+    }
+
+    /// Fail with `MessageTooLong` if the message exceeds `max_message_bytes`
+    fn check_message_len(&self, message: &[u8]) -> Result<(), MappingError> {
+        match self.config.max_message_bytes {
+            Some(max) if message.len() > max => Err(MappingError::MessageTooLong {
+                len: message.len(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn encode_unchecked(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
         // H(m || r)
         let mut input = Vec::new();
         input.extend_from_slice(message);
@@ -192,20 +345,97 @@ impl TL1C {
 
         let hash = self.hasher.hash(&input);
 
-        // Convert hash to integer
-        let mut value = 0usize;
-        for (i, &byte) in hash.iter().enumerate().take(8) {
-            value |= (byte as usize) << (i * 8);
+        // Map to top layers
+        self.try_map_to_top_layers_big(&self.digest_to_index(&hash))
+    }
+
+    /// Reduce the whole hash, read as a little-endian integer, into [0, ℓ_{[0:d₀]})
+    fn digest_to_index(&self, hash: &[u8]) -> BigUint {
+        digest_to_integer(hash) % self.mapper.total_size()
+    }
+}
+
+/// TL1C signature: the WOTS chains together with the signed digest
+/// (a₁, ..., aᵥ, C) in hypercube range [1, w]
+#[derive(Debug, Clone)]
+pub struct Tl1cSignature {
+    w: usize,
+    digest: Vec<usize>,
+    signature: WotsSignature,
+}
+
+impl Tl1cSignature {
+    pub fn new(w: usize, digest: Vec<usize>, signature: WotsSignature) -> Self {
+        Tl1cSignature {
+            w,
+            digest,
+            signature,
         }
+    }
 
-        // Map to top layers
-        self.map_to_top_layers(value)
+    pub fn w(&self) -> usize {
+        self.w
+    }
+
+    pub fn digest(&self) -> &[usize] {
+        &self.digest
     }
+
+    pub fn signature(&self) -> &WotsSignature {
+        &self.signature
+    }
+
+    /// Check that the checksum digit matches the layer of the signed vertex
+    /// Paper Equation (2): C = d + 1 with d = vw - Σaᵢ.
+    /// This does not bind the signature to a message; use `TL1C::verify` for that.
+    pub fn self_consistent(&self) -> bool {
+        let Some((&checksum, components)) = self.digest.split_last() else {
+            return false;
+        };
+
+        if components.is_empty()
+            || self.signature.chains().len() != self.digest.len()
+            || self.digest.iter().any(|x| !(1..=self.w).contains(x))
+        {
+            return false;
+        }
+
+        let vertex = Vertex::new(components.to_vec());
+        let layer = Hypercube::new(self.w, components.len()).calculate_layer(&vertex);
+
+        checksum == layer + 1
+    }
+}
+
+/// Convert hypercube digits in [1, w] to WOTS digits in [0, w - 1]
+fn to_wots_digits(digest: &[usize]) -> Vec<usize> {
+    digest.iter().map(|&x| x - 1).collect()
 }
 
 impl EncodingScheme for TL1C {
+    /// # Panics
+    ///
+    /// Panics on a message longer than `max_message_bytes`, the only input
+    /// `try_encode` rejects once `try_new` has succeeded. `sign` and `verify`
+    /// use the fallible inherent `encode`.
     fn encode(&self, message: &[u8], randomness: &[u8]) -> Vertex {
-        self.encode(message, randomness)
+        TL1C::encode(self, message, randomness)
+            .expect("TL1C encoding failed; use try_encode to handle mapping errors")
+    }
+
+    fn try_encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        TL1C::encode(self, message, randomness)
+    }
+
+    /// WOTS digits of (a₁, ..., aᵥ, C), shifted to [0, w-1]
+    fn encode_to_digest(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Vec<usize>, MappingError> {
+        Ok(to_wots_digits(
+            &self.message_to_wots_digest(message, randomness)?,
+        ))
     }
 
     fn alphabet_size(&self) -> usize {
@@ -215,6 +445,27 @@ impl EncodingScheme for TL1C {
     fn dimension(&self) -> usize {
         self.config.v
     }
+
+    fn name(&self) -> &str {
+        "TL1C"
+    }
+
+    /// The whole hash output is reduced modulo ℓ_{[0:d₀]}
+    fn entropy_bits_consumed(&self) -> usize {
+        self.hasher.output_size() * 8
+    }
+
+    /// Paper Section 2.2: the top layers [0, d₀]
+    fn support_layers(&self) -> RangeInclusive<usize> {
+        0..=self.config.d0
+    }
+
+    fn layer_params(&self) -> LayerParams {
+        LayerParams {
+            d0: self.config.d0,
+            c: 1, // TL1C has a single checksum chain
+        }
+    }
 }
 
 impl NonUniformMapping for TL1C {
@@ -231,7 +482,7 @@ impl NonUniformMapping for TL1C {
 
         if layer <= self.config.d0 {
             // Paper: Uniform distribution within top layers [0, d₀]
-            1.0 / self.total_layer_size as f64
+            1.0 / self.mapper.total_size().to_f64().unwrap_or(f64::INFINITY)
         } else {
             0.0
         }
@@ -241,6 +492,7 @@ impl NonUniformMapping for TL1C {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::hash::SHA256;
 
     #[test]
     fn test_tl1c_config_creation() {
@@ -251,21 +503,12 @@ mod tests {
         assert!(config.v() > 0);
         assert!(config.d0() > 0);
 
-        // Check that total layer size calculation succeeds
-        let mut total_size_option = Some(0usize);
-        for d in 0..=config.d0() {
-            if let Some(current_total) = total_size_option {
-                let layer_size_big = calculate_layer_size(d, config.v(), config.w()).unwrap();
-                if let Some(layer_size) = layer_size_big.to_usize() {
-                    total_size_option = current_total.checked_add(layer_size);
-                } else {
-                    total_size_option = None;
-                    break;
-                }
-            }
-        }
-
-        assert!(total_size_option.is_some() && total_size_option.unwrap() > 0);
+        // The selected support ℓ_{[0:d₀]} reaches 2^128, beyond usize
+        let total: BigUint = (0..=config.d0())
+            .map(|d| calculate_layer_size(d, config.v(), config.w()).unwrap())
+            .sum();
+        assert!(total >= BigUint::from(1u8) << 128);
+        assert!(config.security_bits_estimate() >= 128);
     }
 
     #[test]
@@ -287,7 +530,7 @@ mod tests {
         let message = b"test message";
         let randomness = b"random seed";
 
-        let (encoded, checksum) = tl1c.encode_with_checksum(message, randomness);
+        let (encoded, checksum) = tl1c.encode_with_checksum(message, randomness).unwrap();
 
         // Verify encoded vertex is in valid layer range
         let hc = Hypercube::new(4, 4);
@@ -322,8 +565,8 @@ mod tests {
         let randomness = b"random seed";
 
         // Same input should produce same output
-        let (encoded1, checksum1) = tl1c.encode_with_checksum(message, randomness);
-        let (encoded2, checksum2) = tl1c.encode_with_checksum(message, randomness);
+        let (encoded1, checksum1) = tl1c.encode_with_checksum(message, randomness).unwrap();
+        let (encoded2, checksum2) = tl1c.encode_with_checksum(message, randomness).unwrap();
 
         assert_eq!(encoded1.components(), encoded2.components());
         assert_eq!(checksum1, checksum2);
@@ -388,7 +631,7 @@ mod tests {
         let message = b"test message";
         let randomness = b"random seed";
 
-        let digest = tl1c.message_to_wots_digest(message, randomness);
+        let digest = tl1c.message_to_wots_digest(message, randomness).unwrap();
 
         // Digest should have v+1 elements
         assert_eq!(digest.len(), 5); // v=4 + 1 checksum
@@ -419,4 +662,228 @@ mod tests {
             assert!(checksum <= w, "Checksum should fit in alphabet");
         }
     }
+
+    #[test]
+    fn test_tl1c_verify_digest_rejects_layer_above_d0() {
+        let config = TL1CConfig::with_params(5, 3, 3);
+        let tl1c = TL1C::new(config);
+
+        // Honestly encoded digests are accepted
+        let digest = tl1c
+            .message_to_wots_digest(b"test message", b"random seed")
+            .unwrap();
+        assert!(tl1c.verify_digest(&digest));
+
+        // (4, 4, 3) lies in layer 15 - 11 = 4 = d0 + 1, with checksum 5 = layer + 1
+        let forged = vec![4, 4, 3, 5];
+        assert!(!tl1c.verify_digest(&forged));
+
+        // A vertex in layer d0 itself is still accepted
+        let boundary = vec![5, 4, 3, 4];
+        assert!(tl1c.verify_digest(&boundary));
+    }
+
+    #[test]
+    fn test_tl1c_verify_digest_rejects_tampered_checksum() {
+        let tl1c = TL1C::new(TL1CConfig::with_params(5, 3, 3));
+
+        let digest = tl1c
+            .message_to_wots_digest(b"test message", b"random seed")
+            .unwrap();
+        assert!(tl1c.verify_digest(&digest));
+
+        // Any other in-range checksum value breaks C = d + 1
+        let v = tl1c.config.v;
+        for checksum in (1..=tl1c.config.w).filter(|&c| c != digest[v]) {
+            let mut tampered = digest.clone();
+            tampered[v] = checksum;
+            assert!(!tl1c.verify_digest(&tampered));
+        }
+    }
+
+    #[test]
+    fn test_tl1c_cached_layer_search_matches_linear_scan() {
+        let tl1c = TL1C::new(TL1CConfig::with_params(12, 6, 10));
+        let config = &tl1c.config;
+
+        for value in 0..1000usize {
+            let value = value.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            let index = value % tl1c.mapper.total_size().to_usize().unwrap();
+
+            // Layer found by the original linear scan over calculate_layer_size
+            let mut cumulative = 0;
+            let expected_layer = (0..=config.d0)
+                .find(|&d| {
+                    cumulative += calculate_layer_size(d, config.v, config.w)
+                        .unwrap()
+                        .to_usize()
+                        .unwrap();
+                    index < cumulative
+                })
+                .unwrap();
+
+            let vertex = tl1c.try_map_to_top_layers(value).unwrap();
+            assert_eq!(
+                Hypercube::new(config.w, config.v).calculate_layer(&vertex),
+                expected_layer
+            );
+        }
+    }
+
+    #[test]
+    fn test_tl1c_try_map_to_top_layers_reduces_modulo_support() {
+        let tl1c = TL1C::new(TL1CConfig::with_params(4, 4, 3));
+        let total = tl1c.mapper.total_size().to_usize().unwrap();
+
+        // Every index of the support maps to a distinct top-layer vertex
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..total {
+            let vertex = tl1c.try_map_to_top_layers(i).unwrap();
+            assert_eq!(tl1c.mapper.to_integer(&vertex).unwrap(), i);
+            assert!(seen.insert(vertex.components().clone()));
+        }
+
+        // Larger values wrap around, and the infallible variant agrees
+        for i in 0..total {
+            assert_eq!(
+                tl1c.try_map_to_top_layers(total + i).unwrap(),
+                tl1c.try_map_to_top_layers(i).unwrap()
+            );
+            assert_eq!(
+                tl1c.map_to_top_layers(total + i),
+                tl1c.try_map_to_top_layers(i).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_tl1c_signature_self_consistent() {
+        let config = TL1CConfig::with_params(5, 3, 3);
+        let tl1c = TL1C::new(config);
+        let keypair = WotsKeypair::generate(&crate::wots::WotsParams::new(5, 4));
+
+        let message = b"test message";
+        let randomness = b"random seed";
+        let signature = tl1c.sign(&keypair, message, randomness).unwrap();
+
+        assert!(signature.self_consistent());
+        assert!(tl1c.verify(keypair.public_key(), message, randomness, &signature));
+
+        // Change only the checksum digit: the layer no longer matches
+        let mut digest = signature.digest().to_vec();
+        let last = digest.len() - 1;
+        digest[last] = if digest[last] == 1 {
+            2
+        } else {
+            digest[last] - 1
+        };
+        let tampered = Tl1cSignature::new(5, digest, signature.signature().clone());
+
+        assert!(!tampered.self_consistent());
+    }
+
+    #[test]
+    fn test_tl1c_nonempty_layers() {
+        let config = TL1CConfig::with_params(4, 4, 3);
+        assert!(config.nonempty_layers());
+        assert!(TL1C::try_new(config).is_ok());
+
+        // Every layer d <= v(w-1) of [w]^v is non-empty, so only a d0 past the
+        // last layer (bypassing the constructor checks) leaves a gap
+        let config = TL1CConfig {
+            w: 4,
+            v: 2,
+            d0: 7,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
+        };
+        assert!(!config.nonempty_layers());
+        assert_eq!(
+            TL1C::try_new(config).err(),
+            Some(MappingError::EmptyLayer { d: 7 })
+        );
+    }
+
+    #[test]
+    fn test_tl1c_max_message_bytes() {
+        let config = TL1CConfig::with_params(5, 3, 3).with_max_message_bytes(16);
+        assert_eq!(config.max_message_bytes(), Some(16));
+        let tl1c = TL1C::new(config);
+
+        let randomness = b"random seed";
+        assert!(tl1c.encode(&[0u8; 16], randomness).is_ok());
+        assert_eq!(
+            tl1c.encode(&[0u8; 17], randomness),
+            Err(MappingError::MessageTooLong { len: 17, max: 16 })
+        );
+
+        // Long messages go through the prehashed API instead
+        let message_hash: [u8; 32] = SHA256::new().hash(&[0u8; 17]).try_into().unwrap();
+        assert!(tl1c.encode_prehashed(&message_hash, randomness).is_ok());
+    }
+
+    #[test]
+    fn test_tl1c_over_long_message_neither_signs_nor_verifies() {
+        use crate::wots::WotsParams;
+
+        let tl1c = TL1C::new(TL1CConfig::with_params(5, 3, 3).with_max_message_bytes(16));
+        let keypair = WotsKeypair::generate(&WotsParams::new(5, 4));
+        let too_long = MappingError::MessageTooLong { len: 100, max: 16 };
+
+        assert_eq!(
+            tl1c.sign(&keypair, &[1u8; 100], b"randomness one")
+                .unwrap_err(),
+            WotsError::Encoding(too_long.clone())
+        );
+
+        // A key with the wrong number of chains is an error, not a panic
+        let short_key = WotsKeypair::generate(&WotsParams::new(5, 3));
+        assert_eq!(
+            tl1c.sign(&short_key, b"short", b"randomness one")
+                .unwrap_err(),
+            WotsError::LengthMismatch {
+                expected: 3,
+                actual: 4
+            }
+        );
+        assert_eq!(
+            tl1c.message_to_wots_digest(&[1u8; 100], b"randomness one"),
+            Err(too_long.clone())
+        );
+        assert_eq!(
+            tl1c.try_encode(&[1u8; 100], b"randomness one"),
+            Err(too_long)
+        );
+
+        // A signature on the sink vertex, which over-long messages used to encode to
+        let sink_digest = vec![5, 5, 5, 1];
+        let forged = Tl1cSignature::new(5, sink_digest.clone(), keypair.sign_raw(&[4, 4, 4, 0]));
+        assert!(tl1c.verify_digest(&sink_digest));
+        for (message, randomness) in [
+            (&[1u8; 100][..], &b"randomness one"[..]),
+            (&[2u8; 200][..], &b"randomness two"[..]),
+        ] {
+            assert!(!tl1c.verify(keypair.public_key(), message, randomness, &forged));
+        }
+    }
+
+    #[test]
+    fn test_tl1c_encode_uses_full_digest() {
+        let tl1c = TL1C::new(TL1CConfig::new(128));
+        assert_eq!(tl1c.entropy_bits_consumed(), 256);
+
+        // Two digests agreeing on their first 8 bytes, as for a collision of
+        // the previous 64-bit truncation
+        let first = tl1c.hasher.hash(b"first");
+        let mut second = tl1c.hasher.hash(b"second");
+        second[..8].copy_from_slice(&first[..8]);
+
+        let a = tl1c
+            .try_map_to_top_layers_big(&tl1c.digest_to_index(&first))
+            .unwrap();
+        let b = tl1c
+            .try_map_to_top_layers_big(&tl1c.digest_to_index(&second))
+            .unwrap();
+        assert_ne!(a, b);
+    }
 }
@@ -3,11 +3,15 @@
 /// Paper Construction 2:Top Layers with a Full Checksum
 // TLFC maps messages to multiple layers [0, d₀] with c checksum chains.
 // This provides the best verification efficiency at the cost of c extra chains.
-use crate::core::encoding::{EncodingScheme, NonUniformMapping};
+use crate::core::encoding::{EncodingScheme, LayerParams, NonUniformMapping};
 use crate::core::hypercube::{Hypercube, Vertex};
-use crate::core::mapping::{calculate_layer_size, integer_to_vertex};
-use crate::crypto::hash::{HashFunction, SHA256};
-use num_traits::ToPrimitive;
+use crate::core::mapping::{calculate_layer_size, MappingError, TopLayersMapper};
+use crate::crypto::hash::{HashAlg, HashFunction};
+use crate::schemes::{digest_to_integer, support_bits, ParamError, SchemeConfig};
+use crate::wots::WotsParams;
+use num_bigint::BigUint;
+use num_traits::{ToPrimitive, Zero};
+use std::ops::RangeInclusive;
 
 /// TLFC configuration parameters
 #[derive(Debug, Clone)]
@@ -16,13 +20,14 @@ pub struct TLFCConfig {
     v: usize,
     d0: usize,
     c: usize, // Paper: Number of checksum chains (optimization parameter)
+    bind_randomness: bool,
+    max_message_bytes: Option<usize>,
+    hash: HashAlg,
 }
 
-// TODO: Consider using `num_bigint` for large integers if needed
-// This allows us to handle larger values without overflow issues
-// For now, we use usize for simplicity and performance
 impl TLFCConfig {
     /// Create TLFC config for given security level
+    /// Layer sizes are summed as BigUint, so supports beyond usize qualify.
     pub fn new(security_bits: usize) -> Self {
         // For TLFC, we need ℓ_{[0:d₀]} ≥ 2^λ
         // The number of checksum chains c is an optimization parameter
@@ -31,29 +36,28 @@ impl TLFCConfig {
             (16, 16, 4), // w=16, v=16, c=4
             (32, 12, 3), // w=32, v=12, c=3
             (64, 8, 2),  // w=64, v=8, c=2
+            (16, 48, 4), // w=16, v=48, c=4: reaches 2^128 at d0=96
+            (16, 64, 4), // w=16, v=64, c=4: reaches 2^160 at d0=109
         ];
 
         for (w, v, c) in candidates {
             // Find appropriate d0 such that sum of layer sizes ≥ 2^λ
+            let mut total_size = BigUint::zero();
+            total_size += calculate_layer_size(0, v, w).unwrap();
             for d0 in 1..=(v * (w - 1)) {
-                // Try to calculate total size with overflow protection
-                let mut total_size_option = Some(0usize);
-                for d in 0..=d0 {
-                    if let Some(current_total) = total_size_option {
-                        let layer_size_big = calculate_layer_size(d, v, w).unwrap();
-                        if let Some(layer_size) = layer_size_big.to_usize() {
-                            total_size_option = current_total.checked_add(layer_size);
-                        } else {
-                            total_size_option = None;
-                            break;
-                        }
-                    }
-                }
-
-                if let Some(total_size) = total_size_option {
-                    if total_size > 0 && (total_size as f64).log2() >= security_bits as f64 {
-                        return TLFCConfig { w, v, d0, c };
-                    }
+                total_size += calculate_layer_size(d0, v, w).unwrap();
+
+                // ⌊log₂ total⌋ ≥ λ
+                if total_size.bits() > security_bits as u64 {
+                    return TLFCConfig {
+                        w,
+                        v,
+                        d0,
+                        c,
+                        bind_randomness: false,
+                        max_message_bytes: None,
+                        hash: HashAlg::default(),
+                    };
                 }
             }
         }
@@ -64,18 +68,72 @@ impl TLFCConfig {
             v: 32,
             d0: 10,
             c: 4,
+            bind_randomness: false,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
         }
     }
 
     /// Create TLFC config with specific parameters
+    /// Only available in tests or with the `test-params` feature, since most
+    /// small parameter sets are insecure. Production code must use `new` or
+    /// the explicitly named `with_params_unchecked`.
+    #[cfg(any(test, feature = "test-params"))]
     pub fn with_params(w: usize, v: usize, d0: usize, c: usize) -> Self {
+        Self::with_params_unchecked(w, v, d0, c)
+    }
+
+    /// Create TLFC config with specific parameters, without a security check
+    /// Check `SchemeConfig::security_bits_estimate` before using the result.
+    pub fn with_params_unchecked(w: usize, v: usize, d0: usize, c: usize) -> Self {
         assert!(w > 1, "w must be greater than 1");
         assert!(v > 0, "v must be positive");
         assert!(d0 <= v * (w - 1), "d0 must be valid layer");
         assert!(c > 0, "c must be positive");
         assert!(c <= v, "c cannot exceed v");
 
-        TLFCConfig { w, v, d0, c }
+        TLFCConfig {
+            w,
+            v,
+            d0,
+            c,
+            bind_randomness: false,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
+        }
+    }
+
+    /// Enable or disable binding the checksum to the encoding randomness
+    /// When enabled, each checksum C_i also depends on H(r), so another
+    /// in-support vertex with the same checksum is valid only under the same r.
+    /// This is an extension beyond the paper's Eq. (3).
+    pub fn with_randomness_binding(mut self, enabled: bool) -> Self {
+        self.bind_randomness = enabled;
+        self
+    }
+
+    /// Reject messages longer than `limit` bytes in `encode`
+    /// Bounds the input a verifier buffers; larger messages must be hashed by
+    /// the caller and encoded with `encode_prehashed`.
+    pub fn with_max_message_bytes(mut self, limit: usize) -> Self {
+        self.max_message_bytes = Some(limit);
+        self
+    }
+
+    pub fn max_message_bytes(&self) -> Option<usize> {
+        self.max_message_bytes
+    }
+
+    /// Select the hash used to derive the vertex index (SHA-256 by default)
+    /// Encodings under different hashes are unrelated, so signer and
+    /// verifier must agree on it.
+    pub fn with_hash(mut self, hash: HashAlg) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    pub fn hash(&self) -> HashAlg {
+        self.hash
     }
 
     pub fn w(&self) -> usize {
@@ -94,112 +152,238 @@ impl TLFCConfig {
         self.c
     }
 
+    pub fn binds_randomness(&self) -> bool {
+        self.bind_randomness
+    }
+
+    /// Whether every layer in [0, d0] is non-empty
+    /// The cumulative search in `try_map_to_top_layers` assumes the support
+    /// is this contiguous range of layers.
+    pub fn nonempty_layers(&self) -> bool {
+        self.first_empty_layer().is_none()
+    }
+
+    fn first_empty_layer(&self) -> Option<usize> {
+        (0..=self.d0)
+            .find(|&d| calculate_layer_size(d, self.v, self.w).map_or(true, |size| size.is_zero()))
+    }
+
     pub fn signature_chains(&self) -> usize {
         self.v + self.c // TLFC has c checksum chains
     }
+
+    /// WOTS parameters for signing TLFC digests
+    /// Vertex and checksum chains share one alphabet: components and checksums
+    /// both lie in [1, w], giving WOTS digits in [0, w-1]. Fails unless every
+    /// checksum chain can take more than one digit, i.e. its weight 2^i is not
+    /// a multiple of w, and the c chains of w digits cover every checksum
+    /// value in [0, d0].
+    pub fn wots_params(&self) -> Result<WotsParams, ParamError> {
+        // 2^i mod w, for each checksum chain i
+        let mut weight = 1 % self.w;
+        for chain in 0..self.c {
+            if weight == 0 {
+                return Err(ParamError::ChecksumWeightVanishes { chain, w: self.w });
+            }
+            weight = weight * 2 % self.w;
+        }
+
+        // w^c, or None once it exceeds usize and so any d0
+        let capacity = (0..self.c).try_fold(1usize, |acc, _| acc.checked_mul(self.w));
+        if matches!(capacity, Some(capacity) if capacity <= self.d0) {
+            return Err(ParamError::ChecksumTooSmall {
+                c: self.c,
+                w: self.w,
+                max: self.d0,
+            });
+        }
+
+        Ok(WotsParams::from_tlfc(self))
+    }
+}
+
+impl SchemeConfig for TLFCConfig {
+    fn security_bits_estimate(&self) -> usize {
+        support_bits(self.w, self.v, 0..=self.d0)
+    }
 }
 
 /// TLFC encoding scheme
 /// Paper Algorithm TLFC (Section 2.3): Maps messages to layers [0, d₀] with full checksum
 pub struct TLFC {
     config: TLFCConfig,
-    hasher: SHA256,
-    total_layer_size: usize,
+    hasher: HashAlg,
+    /// Bijection between layers [0, d0] and [0, ℓ_{[0:d₀]})
+    mapper: TopLayersMapper,
 }
 
 impl TLFC {
     pub fn new(config: TLFCConfig) -> Self {
-        // Calculate total size of layers [0, d0] with overflow protection
-        let mut total_layer_size = 0usize;
-        for d in 0..=config.d0 {
-            let layer_size_big = calculate_layer_size(d, config.v, config.w).unwrap();
-            let layer_size = layer_size_big
-                .to_usize()
-                .expect("Layer size too large to fit in usize");
-            total_layer_size = total_layer_size
-                .checked_add(layer_size)
-                .expect("Total layer size overflow");
+        Self::try_new(config).unwrap_or_else(|e| panic!("Invalid TLFC parameters: {:?}", e))
+    }
+
+    /// Fallible constructor
+    /// Rejects a support [0, d0] containing an empty layer.
+    pub fn try_new(config: TLFCConfig) -> Result<Self, MappingError> {
+        if let Some(d) = config.first_empty_layer() {
+            return Err(MappingError::EmptyLayer { d });
         }
 
-        assert!(total_layer_size > 0, "Total layer size must be positive");
+        // Calculate total size of layers [0, d0]
+        let mapper = TopLayersMapper::new(config.w, config.v, config.d0)?;
 
-        TLFC {
+        Ok(TLFC {
+            hasher: config.hash,
             config,
-            hasher: SHA256::new(),
-            total_layer_size,
-        }
+            mapper,
+        })
     }
 
     /// Encode message with full checksum
-    pub fn encode_with_checksum(&self, message: &[u8], randomness: &[u8]) -> (Vertex, Vec<usize>) {
-        let vertex = self.encode(message, randomness);
-        let checksums = self.calculate_full_checksum(vertex.components());
-        (vertex, checksums)
+    pub fn encode_with_checksum(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Vertex, Vec<usize>), MappingError> {
+        let vertex = self.encode(message, randomness)?;
+        let checksums = self.checksums_for(vertex.components(), randomness);
+        Ok((vertex, checksums))
     }
 
     /// Calculate full checksum for vertex components
     /// Full checksum with c chains
     pub fn calculate_full_checksum(&self, components: &[usize]) -> Vec<usize> {
         let w = self.config.w;
+
+        // Paper: Normalize checksums to fit in alphabet [w]
+        // Implementation detail: map to [1, w] range
+        self.raw_checksums(components)
+            .into_iter()
+            .map(|checksum| (checksum % w) + 1)
+            .collect()
+    }
+
+    /// Calculate full checksum bound to the encoding randomness
+    /// C_i = ((Σ_{j: j mod c = i} 2^(j mod c) * (w - a_j) + h_i) mod w) + 1
+    /// where h_i is the i-th byte of H(r), so the checksum depends on (a, r).
+    pub fn calculate_bound_checksum(&self, components: &[usize], randomness: &[u8]) -> Vec<usize> {
+        let w = self.config.w;
+        let binding = self.hasher.hash(randomness);
+
+        self.raw_checksums(components)
+            .into_iter()
+            .enumerate()
+            .map(|(i, checksum)| ((checksum + binding[i % binding.len()] as usize) % w) + 1)
+            .collect()
+    }
+
+    /// Checksums according to the configured mode
+    pub fn checksums_for(&self, components: &[usize], randomness: &[u8]) -> Vec<usize> {
+        if self.config.bind_randomness {
+            self.calculate_bound_checksum(components, randomness)
+        } else {
+            self.calculate_full_checksum(components)
+        }
+    }
+
+    /// Verify that the checksum part of a WOTS digest (a₁, ..., aᵥ, C₁, ..., C_c)
+    /// matches its vertex part, using the configured checksum mode.
+    pub fn verify_checksum(&self, digest: &[usize], randomness: &[u8]) -> bool {
+        let v = self.config.v;
+
+        if digest.len() != self.config.signature_chains() {
+            return false;
+        }
+
+        if digest[..v].iter().any(|x| !(1..=self.config.w).contains(x)) {
+            return false;
+        }
+
+        self.checksums_for(&digest[..v], randomness) == digest[v..]
+    }
+
+    // Paper Eq. (3): C_i = Σ_{j: j mod c = i} 2^(j mod c) * (w - a_j)
+    fn raw_checksums(&self, components: &[usize]) -> Vec<usize> {
+        let w = self.config.w;
         let c = self.config.c;
         let mut checksums = vec![0; c];
 
-        // Paper Eq. (3): C_i = Σ_{j: j mod c = i} 2^(j mod c) * (w - a_j)
         for (j, &a_j) in components.iter().enumerate() {
             let i = j % c;
             checksums[i] += (1 << (j % c)) * (w - a_j);
         }
 
-        // Paper: Normalize checksums to fit in alphabet [w]
-        // Implementation detail: map to [1, w] range
-        for checksum in &mut checksums {
-            *checksum = (*checksum % w) + 1;
-        }
-
         checksums
     }
 
-    /// Map to top layers [0, d0]
+    /// Map to top layers [0, d0], propagating mapping failures
     /// Uniform mapping to the union of layers [0, d₀]
     /// Same distribution as TL1C but with different checksum computation
-    pub fn map_to_top_layers(&self, value: usize) -> Vertex {
+    pub fn try_map_to_top_layers(&self, value: usize) -> Result<Vertex, MappingError> {
+        self.try_map_to_top_layers_big(&BigUint::from(value))
+    }
+
+    /// Map an integer of any size to top layers [0, d0]
+    pub fn try_map_to_top_layers_big(&self, value: &BigUint) -> Result<Vertex, MappingError> {
         // Map uniformly to layers [0, d0]
-        let index = value % self.total_layer_size;
-
-        // Find which layer this index falls into
-        let mut cumulative = 0;
-        for d in 0..=self.config.d0 {
-            let layer_size = calculate_layer_size(d, self.config.v, self.config.w)
-                .unwrap()
-                .to_usize()
-                .unwrap();
-            if index < cumulative + layer_size {
-                // Index is in layer d
-                let layer_index = index - cumulative;
-                let components = integer_to_vertex(layer_index, self.config.w, self.config.v, d)
-                    .unwrap_or_else(|_| vec![self.config.w; self.config.v]);
-                return Vertex::new(components);
-            }
-            cumulative += layer_size;
-        }
+        let index = value % self.mapper.total_size();
 
-        // Should not reach here
-        panic!("Index out of range");
+        self.mapper.to_vertex_big(&index)
+    }
+
+    /// Map to top layers [0, d0]
+    /// Cannot fail: `try_new` builds the mapper for this config, and the value
+    /// is reduced into its support before mapping.
+    pub fn map_to_top_layers(&self, value: usize) -> Vertex {
+        self.try_map_to_top_layers(value)
+            .expect("index reduced into the support always maps to a vertex")
     }
 
     /// Convert message to WOTS digest including checksums
     /// The WOTS message is (a₁, ..., aᵥ, C₁, ..., C_c)
     /// where (a₁, ..., aᵥ) is the encoded vertex and C₁, ..., C_c are the checksums.
-    pub fn message_to_wots_digest(&self, message: &[u8], randomness: &[u8]) -> Vec<usize> {
-        let (vertex, checksums) = self.encode_with_checksum(message, randomness);
+    pub fn message_to_wots_digest(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Vec<usize>, MappingError> {
+        let (vertex, checksums) = self.encode_with_checksum(message, randomness)?;
 
         let mut digest = vertex.components().clone();
         digest.extend(checksums);
 
-        digest
+        Ok(digest)
     }
 
-    fn encode(&self, message: &[u8], randomness: &[u8]) -> Vertex {
+    /// Encode message and randomness to a vertex in layers [0, d0]
+    pub fn encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        self.check_message_len(message)?;
+        self.encode_unchecked(message, randomness)
+    }
+
+    /// Encode a caller-computed 32-byte message hash, for messages of any length
+    /// Not subject to `max_message_bytes`. Signer and verifier must both use
+    /// the prehashed form, since it encodes H(m) in place of m.
+    pub fn encode_prehashed(
+        &self,
+        message_hash: &[u8; 32],
+        randomness: &[u8],
+    ) -> Result<Vertex, MappingError> {
+        self.encode_unchecked(message_hash, randomness)
+    }
+
+    /// Fail with `MessageTooLong` if the message exceeds `max_message_bytes`
+    fn check_message_len(&self, message: &[u8]) -> Result<(), MappingError> {
+        match self.config.max_message_bytes {
+            Some(max) if message.len() > max => Err(MappingError::MessageTooLong {
+                len: message.len(),
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn encode_unchecked(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
         // H(m || r)
         let mut input = Vec::new();
         input.extend_from_slice(message);
@@ -207,20 +391,42 @@ impl TLFC {
 
         let hash = self.hasher.hash(&input);
 
-        // Convert hash to integer
-        let mut value = 0usize;
-        for (i, &byte) in hash.iter().enumerate().take(8) {
-            value |= (byte as usize) << (i * 8);
-        }
-
         // Map to top layers
-        self.map_to_top_layers(value)
+        self.try_map_to_top_layers_big(&self.digest_to_index(&hash))
+    }
+
+    /// Reduce the whole hash, read as a little-endian integer, into [0, ℓ_{[0:d₀]})
+    fn digest_to_index(&self, hash: &[u8]) -> BigUint {
+        digest_to_integer(hash) % self.mapper.total_size()
     }
 }
 
 impl EncodingScheme for TLFC {
+    /// # Panics
+    ///
+    /// Panics on a message longer than `max_message_bytes`, the only input
+    /// `try_encode` rejects once `try_new` has succeeded.
+    /// `message_to_wots_digest` uses the fallible inherent `encode`.
     fn encode(&self, message: &[u8], randomness: &[u8]) -> Vertex {
-        self.encode(message, randomness)
+        TLFC::encode(self, message, randomness)
+            .expect("TLFC encoding failed; use try_encode to handle mapping errors")
+    }
+
+    fn try_encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        TLFC::encode(self, message, randomness)
+    }
+
+    /// WOTS digits of (a₁, ..., aᵥ, C₁, ..., C_c), shifted to [0, w-1]
+    fn encode_to_digest(
+        &self,
+        message: &[u8],
+        randomness: &[u8],
+    ) -> Result<Vec<usize>, MappingError> {
+        Ok(self
+            .message_to_wots_digest(message, randomness)?
+            .iter()
+            .map(|&x| x - 1)
+            .collect())
     }
 
     fn alphabet_size(&self) -> usize {
@@ -230,6 +436,27 @@ impl EncodingScheme for TLFC {
     fn dimension(&self) -> usize {
         self.config.v
     }
+
+    fn name(&self) -> &str {
+        "TLFC"
+    }
+
+    /// The whole hash output is reduced modulo ℓ_{[0:d₀]}
+    fn entropy_bits_consumed(&self) -> usize {
+        self.hasher.output_size() * 8
+    }
+
+    /// Paper Section 2.2: the top layers [0, d₀]
+    fn support_layers(&self) -> RangeInclusive<usize> {
+        0..=self.config.d0
+    }
+
+    fn layer_params(&self) -> LayerParams {
+        LayerParams {
+            d0: self.config.d0,
+            c: self.config.c,
+        }
+    }
 }
 
 impl NonUniformMapping for TLFC {
@@ -246,7 +473,7 @@ impl NonUniformMapping for TLFC {
 
         if layer <= self.config.d0 {
             // Paper: Uniform distribution within top layers [0, d₀]
-            1.0 / self.total_layer_size as f64
+            1.0 / self.mapper.total_size().to_f64().unwrap_or(f64::INFINITY)
         } else {
             0.0
         }
@@ -256,6 +483,7 @@ impl NonUniformMapping for TLFC {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::hash::SHA256;
 
     #[test]
     fn test_tlfc_config_creation() {
@@ -267,21 +495,12 @@ mod tests {
         assert!(config.d0() > 0);
         assert!(config.c() > 0); // Number of checksum chains
 
-        // Check that total layer size calculation succeeds
-        let mut total_size_option = Some(0usize);
-        for d in 0..=config.d0() {
-            if let Some(current_total) = total_size_option {
-                let layer_size_big = calculate_layer_size(d, config.v(), config.w()).unwrap();
-                if let Some(layer_size) = layer_size_big.to_usize() {
-                    total_size_option = current_total.checked_add(layer_size);
-                } else {
-                    total_size_option = None;
-                    break;
-                }
-            }
-        }
-
-        assert!(total_size_option.is_some() && total_size_option.unwrap() > 0);
+        // The selected support ℓ_{[0:d₀]} reaches 2^128, beyond usize
+        let total: BigUint = (0..=config.d0())
+            .map(|d| calculate_layer_size(d, config.v(), config.w()).unwrap())
+            .sum();
+        assert!(total >= BigUint::from(1u8) << 128);
+        assert!(config.security_bits_estimate() >= 128);
     }
 
     #[test]
@@ -304,7 +523,7 @@ mod tests {
         let message = b"test message";
         let randomness = b"random seed";
 
-        let (encoded, checksums) = tlfc.encode_with_checksum(message, randomness);
+        let (encoded, checksums) = tlfc.encode_with_checksum(message, randomness).unwrap();
 
         // Verify encoded vertex is in valid layer range
         let hc = Hypercube::new(8, 4);
@@ -342,8 +561,8 @@ mod tests {
         let randomness = b"random seed";
 
         // Same input should produce same output
-        let (encoded1, checksums1) = tlfc.encode_with_checksum(message, randomness);
-        let (encoded2, checksums2) = tlfc.encode_with_checksum(message, randomness);
+        let (encoded1, checksums1) = tlfc.encode_with_checksum(message, randomness).unwrap();
+        let (encoded2, checksums2) = tlfc.encode_with_checksum(message, randomness).unwrap();
 
         assert_eq!(encoded1.components(), encoded2.components());
         assert_eq!(checksums1, checksums2);
@@ -408,7 +627,7 @@ mod tests {
         let message = b"test message";
         let randomness = b"random seed";
 
-        let digest = tlfc.message_to_wots_digest(message, randomness);
+        let digest = tlfc.message_to_wots_digest(message, randomness).unwrap();
 
         // Digest should have v+c elements
         assert_eq!(digest.len(), 6); // v=4 + c=2
@@ -460,4 +679,232 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_tlfc_cached_layer_search_matches_linear_scan() {
+        let tlfc = TLFC::new(TLFCConfig::with_params(8, 6, 10, 2));
+        let config = &tlfc.config;
+
+        for value in 0..1000usize {
+            let value = value.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            let index = value % tlfc.mapper.total_size().to_usize().unwrap();
+
+            // Layer found by the original linear scan over calculate_layer_size
+            let mut cumulative = 0;
+            let expected_layer = (0..=config.d0)
+                .find(|&d| {
+                    cumulative += calculate_layer_size(d, config.v, config.w)
+                        .unwrap()
+                        .to_usize()
+                        .unwrap();
+                    index < cumulative
+                })
+                .unwrap();
+
+            let vertex = tlfc.try_map_to_top_layers(value).unwrap();
+            assert_eq!(
+                Hypercube::new(config.w, config.v).calculate_layer(&vertex),
+                expected_layer
+            );
+        }
+    }
+
+    #[test]
+    fn test_tlfc_try_map_to_top_layers_reduces_modulo_support() {
+        let tlfc = TLFC::new(TLFCConfig::with_params(8, 4, 3, 2));
+        let total = tlfc.mapper.total_size().to_usize().unwrap();
+
+        // Every index of the support maps to a distinct top-layer vertex
+        let mut seen = std::collections::HashSet::new();
+        for i in 0..total {
+            let vertex = tlfc.try_map_to_top_layers(i).unwrap();
+            assert_eq!(tlfc.mapper.to_integer(&vertex).unwrap(), i);
+            assert!(seen.insert(vertex.components().clone()));
+        }
+
+        // Larger values wrap around, and the infallible variant agrees
+        for i in 0..total {
+            assert_eq!(
+                tlfc.try_map_to_top_layers(total + i).unwrap(),
+                tlfc.try_map_to_top_layers(i).unwrap()
+            );
+            assert_eq!(
+                tlfc.map_to_top_layers(total + i),
+                tlfc.try_map_to_top_layers(i).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_tlfc_randomness_bound_checksum() {
+        let config = TLFCConfig::with_params(8, 4, 3, 2).with_randomness_binding(true);
+        assert!(config.binds_randomness());
+        let tlfc = TLFC::new(config);
+
+        let components = vec![7, 8, 6, 8];
+        let checksums1 = tlfc.calculate_bound_checksum(&components, b"randomness one");
+        let checksums2 = tlfc.calculate_bound_checksum(&components, b"randomness two");
+
+        // Same components, different randomness: different checksums
+        assert_ne!(checksums1, checksums2);
+
+        // Deterministic and within [1, w]
+        assert_eq!(
+            checksums1,
+            tlfc.calculate_bound_checksum(&components, b"randomness one")
+        );
+        for &checksum in checksums1.iter().chain(checksums2.iter()) {
+            assert!((1..=8).contains(&checksum));
+        }
+
+        // The digest verifies only under the randomness it was bound to
+        let digest = tlfc
+            .message_to_wots_digest(b"test message", b"randomness one")
+            .unwrap();
+        assert!(tlfc.verify_checksum(&digest, b"randomness one"));
+        assert!(!tlfc.verify_checksum(&digest, b"randomness two"));
+    }
+
+    #[test]
+    fn test_tlfc_unbound_checksum_ignores_randomness() {
+        let tlfc = TLFC::new(TLFCConfig::with_params(8, 4, 3, 2));
+        let components = vec![7, 8, 6, 8];
+
+        assert_eq!(
+            tlfc.checksums_for(&components, b"randomness one"),
+            tlfc.calculate_full_checksum(&components)
+        );
+        assert_eq!(
+            tlfc.checksums_for(&components, b"randomness one"),
+            tlfc.checksums_for(&components, b"randomness two")
+        );
+
+        let digest = tlfc
+            .message_to_wots_digest(b"test message", b"random seed")
+            .unwrap();
+        assert!(tlfc.verify_checksum(&digest, b"anything"));
+    }
+
+    #[test]
+    fn test_tlfc_support_layers() {
+        let tlfc = TLFC::new(TLFCConfig::with_params(4, 4, 3, 2));
+        assert_eq!(tlfc.support_layers(), 0..=3);
+    }
+
+    #[test]
+    fn test_tlfc_nonempty_layers() {
+        let config = TLFCConfig::with_params(8, 4, 3, 2);
+        assert!(config.nonempty_layers());
+        assert!(TLFC::try_new(config).is_ok());
+
+        // Layers past v(w-1) are empty
+        let config = TLFCConfig {
+            w: 4,
+            v: 2,
+            d0: 7,
+            c: 2,
+            bind_randomness: false,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
+        };
+        assert!(!config.nonempty_layers());
+        assert_eq!(
+            TLFC::try_new(config).err(),
+            Some(MappingError::EmptyLayer { d: 7 })
+        );
+    }
+
+    #[test]
+    fn test_tlfc_wots_params() {
+        let config = TLFCConfig::with_params(4, 8, 10, 2);
+        let params = config.wots_params().unwrap();
+
+        assert_eq!(params.chains(), 8 + 2);
+        assert_eq!(params.w(), config.w());
+
+        // Every digit of an encoded digest is signable under the derived params
+        let tlfc = TLFC::new(config);
+        let keypair = crate::wots::WotsKeypair::generate(&params);
+        let digest = tlfc
+            .encode_to_digest(b"test message", b"random seed")
+            .unwrap();
+        assert!(keypair.try_sign_raw(&digest).is_ok());
+
+        // Chain 2 has weight 4 ≡ 0 mod 4, so its checksum digit is constant
+        assert_eq!(
+            TLFCConfig::with_params(4, 8, 10, 3).wots_params(),
+            Err(ParamError::ChecksumWeightVanishes { chain: 2, w: 4 })
+        );
+
+        // One chain of 5 digits cannot tell checksums 0..=10 apart
+        assert_eq!(
+            TLFCConfig::with_params(5, 8, 10, 1).wots_params(),
+            Err(ParamError::ChecksumTooSmall {
+                c: 1,
+                w: 5,
+                max: 10
+            })
+        );
+
+        assert!(TLFCConfig::new(128).wots_params().is_ok());
+    }
+
+    #[test]
+    fn test_tlfc_max_message_bytes() {
+        let config = TLFCConfig::with_params(4, 8, 10, 2).with_max_message_bytes(16);
+        assert_eq!(config.max_message_bytes(), Some(16));
+        let tlfc = TLFC::new(config);
+
+        let randomness = b"random seed";
+        assert!(tlfc.encode(&[0u8; 16], randomness).is_ok());
+        assert_eq!(
+            tlfc.encode(&[0u8; 17], randomness),
+            Err(MappingError::MessageTooLong { len: 17, max: 16 })
+        );
+
+        // Long messages go through the prehashed API instead
+        let message_hash: [u8; 32] = SHA256::new().hash(&[0u8; 17]).try_into().unwrap();
+        assert!(tlfc.encode_prehashed(&message_hash, randomness).is_ok());
+    }
+
+    #[test]
+    fn test_tlfc_over_long_message_has_no_digest() {
+        let tlfc = TLFC::new(TLFCConfig::with_params(4, 8, 10, 2).with_max_message_bytes(16));
+        let too_long = MappingError::MessageTooLong { len: 100, max: 16 };
+
+        for (message, randomness) in [
+            (&[1u8; 100][..], &b"randomness one"[..]),
+            (&[2u8; 100][..], &b"randomness two"[..]),
+        ] {
+            assert_eq!(
+                tlfc.message_to_wots_digest(message, randomness),
+                Err(too_long.clone())
+            );
+            assert_eq!(
+                tlfc.encode_with_checksum(message, randomness),
+                Err(too_long.clone())
+            );
+            assert_eq!(tlfc.try_encode(message, randomness), Err(too_long.clone()));
+        }
+    }
+
+    #[test]
+    fn test_tlfc_encode_uses_full_digest() {
+        let tlfc = TLFC::new(TLFCConfig::new(128));
+        assert_eq!(tlfc.entropy_bits_consumed(), 256);
+
+        // Two digests agreeing on their first 8 bytes, as for a collision of
+        // the previous 64-bit truncation
+        let first = tlfc.hasher.hash(b"first");
+        let mut second = tlfc.hasher.hash(b"second");
+        second[..8].copy_from_slice(&first[..8]);
+
+        let a = tlfc
+            .try_map_to_top_layers_big(&tlfc.digest_to_index(&first))
+            .unwrap();
+        let b = tlfc
+            .try_map_to_top_layers_big(&tlfc.digest_to_index(&second))
+            .unwrap();
+        assert_ne!(a, b);
+    }
 }
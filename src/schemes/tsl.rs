@@ -4,12 +4,42 @@
 // TSL is the simplest scheme that maps messages to a single layer d₀.
 // It achieves optimal collision resistance with no checksum overhead.
 
-use crate::core::encoding::{EncodingScheme, NonUniformMapping};
+use crate::core::encoding::{EncodingScheme, LayerParams, NonUniformMapping};
 use crate::core::hypercube::Vertex;
-use crate::core::mapping::{calculate_layer_size, integer_to_vertex};
-use crate::crypto::hash::{HashFunction, SHA256};
+use crate::core::mapping::{calculate_layer_size, integer_to_vertex_big, MappingError};
+use crate::crypto::hash::{HashAlg, HashFunction};
+use crate::schemes::{digest_to_integer, support_bits, ParamError, SchemeConfig};
 use num_bigint::BigUint;
 use num_traits::{ToPrimitive, Zero};
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+/// Paper parameters for TSL at 128-bit security: (w, v, optimal_d0)
+const PAPER_PARAMS_128: [(usize, usize, usize); 10] = [
+    (86, 25, 384),
+    (44, 30, 235),
+    (26, 35, 168),
+    (20, 40, 131),
+    (18, 45, 108),
+    (14, 50, 93),
+    (10, 55, 83),
+    (8, 64, 70),
+    (6, 84, 54),
+    (4, 132, 39),
+];
+
+/// Paper parameters for TSL at 160-bit security: (w, v, optimal_d0)
+const PAPER_PARAMS_160: [(usize, usize, usize); 9] = [
+    (56, 35, 337),
+    (44, 40, 245),
+    (28, 45, 193),
+    (21, 50, 160),
+    (14, 60, 121),
+    (13, 70, 99),
+    (8, 80, 86),
+    (6, 104, 67),
+    (4, 168, 48),
+];
 
 /// TSL configuration parameters
 /// Parameters for the TSL encoding scheme
@@ -18,6 +48,9 @@ pub struct TSLConfig {
     w: usize,
     v: usize,
     d0: usize,
+    security_bits: usize,
+    max_message_bytes: Option<usize>,
+    hash: HashAlg,
 }
 
 impl TSLConfig {
@@ -39,38 +72,7 @@ impl TSLConfig {
     pub fn new_full(security_bits: usize, v: usize) -> Self {
         assert!(security_bits == 128 || security_bits == 160, "Only 128-bit and 160-bit security levels are supported");
 
-        // Paper parameters for TSL (w, v, optimal_d0)
-        let paper_params_128 = [
-            (86, 25, 384),
-            (44, 30, 235),
-            (26, 35, 168),
-            (20, 40, 131),
-            (18, 45, 108),
-            (14, 50, 93),
-            (10, 55, 83),
-            (8, 64, 70),
-            (6, 84, 54),
-            (4, 132, 39),
-        ];
-
-        let paper_params_160 = [
-            (56, 35, 337),
-            (44, 40, 245),
-            (28, 45, 193),
-            (21, 50, 160),
-            (14, 60, 121),
-            (13, 70, 99),
-            (8, 80, 86),
-            (6, 104, 67),
-            (4, 168, 48),
-        ];
-
-        // Choose parameter set based on security level
-        let params = if security_bits == 128 {
-            &paper_params_128[..]
-        } else {
-            &paper_params_160[..]
-        };
+        let params = Self::paper_parameters(security_bits);
 
         // Find best match for requested v
         let mut best_match = None;
@@ -130,23 +132,131 @@ impl TSLConfig {
                 w,
                 v,
                 d0: adjusted_d0,
+                security_bits,
+                max_message_bytes: None,
+                hash: HashAlg::default(),
             }
         } else {
             // Fallback: use conservative parameters
             let w = if security_bits <= 128 { 4 } else { 6 };
             let d0 = v * (w - 1) / 2; // Use middle layer
 
-            TSLConfig { w, v, d0 }
+            TSLConfig {
+                w,
+                v,
+                d0,
+                security_bits,
+                max_message_bytes: None,
+                hash: HashAlg::default(),
+            }
+        }
+    }
+
+    /// The paper's (w, v, d₀) table for a security level
+    /// Empty for levels other than 128 and 160 bits, which the paper does not tabulate.
+    /// Every entry passes `validate` at its level.
+    pub fn paper_parameters(security_bits: usize) -> &'static [(usize, usize, usize)] {
+        match security_bits {
+            128 => &PAPER_PARAMS_128,
+            160 => &PAPER_PARAMS_160,
+            _ => &[],
         }
     }
 
+    /// Check this parameter set against its security level λ
+    /// Requires d₀ ≤ v(w-1), a hypercube with log₂ wᵛ ≥ λ + log₂(λ)/2 (the
+    /// collision bound `new_full` selects by), and a layer with ℓ_{d₀} ≈ 2^λ for
+    /// preimage security. The paper tables take the layer whose size is nearest
+    /// 2^λ, some a fraction of a bit short, so log₂ ℓ_{d₀} must round to λ or more.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        let max = self.v * (self.w - 1);
+        if self.d0 > max {
+            return Err(ParamError::LayerOutOfRange { d0: self.d0, max });
+        }
+
+        let lambda = self.security_bits as f64;
+        let collision_bits = lambda + lambda.log2() / 2.0;
+        let hypercube_bits = self.v as f64 * (self.w as f64).log2();
+        if hypercube_bits < collision_bits {
+            return Err(ParamError::HypercubeTooSmall {
+                bits: hypercube_bits.floor() as usize,
+                required: collision_bits.ceil() as usize,
+            });
+        }
+
+        let layer_size = calculate_layer_size(self.d0, self.v, self.w).unwrap_or_default();
+        let layer_bits = layer_size.to_f64().map_or(f64::NEG_INFINITY, f64::log2);
+        if layer_bits.round() < lambda {
+            return Err(ParamError::LayerTooSmall {
+                bits: layer_size.bits().saturating_sub(1) as usize,
+                required: self.security_bits,
+            });
+        }
+
+        Ok(())
+    }
+
     /// Create TSL config with specific parameters
+    /// Only available in tests or with the `test-params` feature, since most
+    /// small parameter sets are insecure. Production code must use `new` or
+    /// the explicitly named `with_params_unchecked`.
+    #[cfg(any(test, feature = "test-params"))]
     pub fn with_params(w: usize, v: usize, d0: usize) -> Self {
+        Self::with_params_unchecked(w, v, d0)
+    }
+
+    /// Create TSL config with specific parameters, without a security check
+    /// The target level is 128 bits unless set with `with_security_bits`.
+    /// Check `validate` or `SchemeConfig::security_bits_estimate` before using
+    /// the result.
+    pub fn with_params_unchecked(w: usize, v: usize, d0: usize) -> Self {
         assert!(w > 1, "w must be greater than 1");
         assert!(v > 0, "v must be positive");
         assert!(d0 <= v * (w - 1), "d0 must be valid layer");
 
-        TSLConfig { w, v, d0 }
+        TSLConfig {
+            w,
+            v,
+            d0,
+            security_bits: 128,
+            max_message_bytes: None,
+            hash: HashAlg::default(),
+        }
+    }
+
+    /// Set the security level λ that `validate` checks against
+    pub fn with_security_bits(mut self, security_bits: usize) -> Self {
+        self.security_bits = security_bits;
+        self
+    }
+
+    /// Target security level λ in bits
+    pub fn security_bits(&self) -> usize {
+        self.security_bits
+    }
+
+    /// Reject messages longer than `limit` bytes in `encode`
+    /// Bounds the input a verifier buffers; larger messages must be hashed by
+    /// the caller and encoded with `encode_prehashed`.
+    pub fn with_max_message_bytes(mut self, limit: usize) -> Self {
+        self.max_message_bytes = Some(limit);
+        self
+    }
+
+    pub fn max_message_bytes(&self) -> Option<usize> {
+        self.max_message_bytes
+    }
+
+    /// Select the hash used to derive the vertex index (SHA-256 by default)
+    /// Encodings under different hashes are unrelated, so signer and
+    /// verifier must agree on it.
+    pub fn with_hash(mut self, hash: HashAlg) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    pub fn hash(&self) -> HashAlg {
+        self.hash
     }
 
     pub fn w(&self) -> usize {
@@ -166,11 +276,17 @@ impl TSLConfig {
     }
 }
 
+impl SchemeConfig for TSLConfig {
+    fn security_bits_estimate(&self) -> usize {
+        support_bits(self.w, self.v, self.d0..=self.d0)
+    }
+}
+
 /// TSL encoding scheme
 /// Maps messages to layer d₀
 pub struct TSL {
     config: TSLConfig,
-    hasher: SHA256,
+    hasher: HashAlg,
     layer_size: BigUint,
 }
 
@@ -184,124 +300,130 @@ impl TSL {
         assert!(!layer_size.is_zero(), "Layer d0 must have positive size");
 
         TSL {
+            hasher: config.hash,
             config,
-            hasher: SHA256::new(),
             layer_size,
         }
     }
 
     /// Map an integer to a vertex in layer d0
-    /// uniformly to vertices in layer d₀.
-    pub fn map_to_layer(&self, value: usize) -> Result<Vertex, crate::core::mapping::MappingError> {
-        // Handle zero layer size case
+    /// Reduces the value modulo ℓ_{d₀} and applies the MapToVertex bijection,
+    /// so distinct indices always give distinct vertices.
+    pub fn map_to_layer(&self, value: usize) -> Result<Vertex, MappingError> {
+        self.map_to_layer_big(&BigUint::from(value))
+    }
+
+    /// Map an integer of any size to a vertex in layer d0
+    /// Paper Section 4.3: with z uniform over a range much larger than ℓ_{d₀},
+    /// z mod ℓ_{d₀} is close to uniform, including for layers beyond usize.
+    pub fn map_to_layer_big(&self, value: &BigUint) -> Result<Vertex, MappingError> {
         if self.layer_size.is_zero() {
-            return Err(crate::core::mapping::MappingError::InvalidLayer {
-                expected: self.config.d0,
-                actual: 0,
-            });
+            return Err(MappingError::EmptyLayer { d: self.config.d0 });
         }
 
-        // For very large layer sizes, we need to use modular arithmetic
-        // to map the input value to a valid range
-        let mapped_index = if let Some(layer_size_usize) = self.layer_size.to_usize() {
-            if layer_size_usize == 0 {
-                return Err(crate::core::mapping::MappingError::InvalidLayer {
-                    expected: self.config.d0,
-                    actual: 0,
-                });
-            }
-            value % layer_size_usize
-        } else {
-            // For very large layer sizes that don't fit in usize,
-            // we use a hash-based approach to map uniformly across a smaller range
-            // while maintaining cryptographic properties
-
-            // Use a combination of value and layer parameters to get good distribution
-            let hash_input = value
-                .wrapping_mul(2654435761) // Use a large prime multiplier
-                .wrapping_add(self.config.d0.wrapping_mul(2246822507))
-                .wrapping_add(self.config.v.wrapping_mul(3266489917))
-                .wrapping_add(self.config.w.wrapping_mul(668265263));
-
-            // Map to a reasonable range that integer_to_vertex can handle
-            // Use a range that's small enough to work but large enough for good distribution
-            let max_safe_index = (self.config.v * self.config.w).min(10000);
-            hash_input % max_safe_index
-        };
+        let index = value % &self.layer_size;
+        let components =
+            integer_to_vertex_big(&index, self.config.w, self.config.v, self.config.d0)?;
+        Ok(Vertex::new(components))
+    }
 
-        // Now try to map this index to a vertex
-        // For very large layers, we need to be more conservative
-        let mut attempts = 0;
-        let mut current_index = mapped_index;
-
-        loop {
-            match integer_to_vertex(current_index, self.config.w, self.config.v, self.config.d0) {
-                Ok(components) => return Ok(Vertex::new(components)),
-                Err(crate::core::mapping::MappingError::IndexOutOfRange { .. }) => {
-                    // Try progressively smaller indices
-                    attempts += 1;
-                    if attempts > 20 {
-                        break;
-                    }
-                    current_index = current_index / 2;
-                    if current_index == 0 && attempts == 1 {
-                        // If even 0 doesn't work, there might be an issue with the layer
-                        break;
-                    }
-                }
-                Err(e) => {
-                    // For other errors, try a few smaller values then give up
-                    attempts += 1;
-                    if attempts > 5 {
-                        return Err(e);
-                    }
-                    current_index = if current_index > 0 {
-                        current_index - 1
-                    } else {
-                        0
-                    };
-                }
+    /// Derive the integer fed to `map_to_layer_big` from message and randomness
+    /// The full hash H(m || r) read as a little-endian integer.
+    /// Signer and verifier must obtain the same value from the same inputs.
+    pub fn encode_integer_big(&self, message: &[u8], randomness: &[u8]) -> BigUint {
+        // Paper Algorithm TSL Step 1: Compute H(m || r)
+        let mut input = Vec::new();
+        input.extend_from_slice(message);
+        input.extend_from_slice(randomness);
+
+        digest_to_integer(&self.hasher.hash(&input))
+    }
+
+    /// Low 64 bits of `encode_integer_big`, for use with `map_to_layer`
+    pub fn encode_integer(&self, message: &[u8], randomness: &[u8]) -> usize {
+        let digits = self.encode_integer_big(message, randomness).to_u64_digits();
+        digits.first().copied().unwrap_or(0) as usize
+    }
+
+    /// Count the distinct vertices reached by encoding `samples` distinct messages
+    /// A diagnostic for mapping quality: a healthy encoder approaches
+    /// min(samples, ℓ_{d₀}). Messages that fail to encode are not counted.
+    pub fn coverage(&self, samples: usize) -> usize {
+        let randomness = [0u8; 32];
+        let mut seen = HashSet::new();
+
+        for i in 0..samples {
+            let message = (i as u64).to_be_bytes();
+            if let Ok(vertex) = TSL::encode(self, &message, &randomness) {
+                seen.insert(vertex.components().clone());
             }
         }
 
-        // If we get here, try the most conservative approach: use index 0
-        match integer_to_vertex(0, self.config.w, self.config.v, self.config.d0) {
-            Ok(components) => Ok(Vertex::new(components)),
-            Err(e) => Err(e),
-        }
+        seen.len()
+    }
+
+    /// Search for a (message, randomness) pair that fails to encode
+    /// Tries the same inputs as `coverage`, returning the first one that
+    /// `TSL::encode` rejects, as a reproducer for bug reports.
+    pub fn find_fallback_input(&self, max_tries: usize) -> Option<(Vec<u8>, Vec<u8>)> {
+        let randomness = [0u8; 32];
+
+        (0..max_tries)
+            .map(|i| (i as u64).to_be_bytes())
+            .find(|message| TSL::encode(self, message, &randomness).is_err())
+            .map(|message| (message.to_vec(), randomness.to_vec()))
     }
 
     /// Encode message and randomness to vertex
-    pub fn encode(
+    pub fn encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        self.check_message_len(message)?;
+        self.encode_unchecked(message, randomness)
+    }
+
+    /// Encode a caller-computed 32-byte message hash, for messages of any length
+    /// Not subject to `max_message_bytes`. Signer and verifier must both use
+    /// the prehashed form, since it encodes H(m) in place of m.
+    pub fn encode_prehashed(
         &self,
-        message: &[u8],
+        message_hash: &[u8; 32],
         randomness: &[u8],
-    ) -> Result<Vertex, crate::core::mapping::MappingError> {
-        // Paper Algorithm TSL Step 1: Compute H(m || r)
-        let mut input = Vec::new();
-        input.extend_from_slice(message);
-        input.extend_from_slice(randomness);
-
-        let hash = self.hasher.hash(&input);
+    ) -> Result<Vertex, MappingError> {
+        self.encode_unchecked(message_hash, randomness)
+    }
 
-        // Convert hash to integer
-        let mut value = 0usize;
-        for (i, &byte) in hash.iter().enumerate().take(8) {
-            value |= (byte as usize) << (i * 8);
+    /// Fail with `MessageTooLong` if the message exceeds `max_message_bytes`
+    fn check_message_len(&self, message: &[u8]) -> Result<(), MappingError> {
+        match self.config.max_message_bytes {
+            Some(max) if message.len() > max => Err(MappingError::MessageTooLong {
+                len: message.len(),
+                max,
+            }),
+            _ => Ok(()),
         }
+    }
+
+    fn encode_unchecked(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        let value = self.encode_integer_big(message, randomness);
 
         // Paper Algorithm TSL Step 2: Map hash output to layer d₀ using Ψ
-        self.map_to_layer(value)
+        self.map_to_layer_big(&value)
     }
 }
 
 impl EncodingScheme for TSL {
+    /// # Panics
+    ///
+    /// Panics if the message cannot be mapped to layer d₀: a message longer
+    /// than `max_message_bytes`, or any message when layer d₀ is empty.
+    /// Retrying gives the same result. Substituting a vertex outside layer d₀,
+    /// such as the sink, would make every such message share one signature.
     fn encode(&self, message: &[u8], randomness: &[u8]) -> Vertex {
-        // Call the TSL-specific encode method and handle errors
-        TSL::encode(self, message, randomness).unwrap_or_else(|_| {
-            // Fallback to sink vertex if mapping fails
-            Vertex::new(vec![self.config.w; self.config.v])
-        })
+        TSL::encode(self, message, randomness)
+            .expect("TSL encoding failed; use try_encode to handle mapping errors")
+    }
+
+    fn try_encode(&self, message: &[u8], randomness: &[u8]) -> Result<Vertex, MappingError> {
+        TSL::encode(self, message, randomness)
     }
 
     fn alphabet_size(&self) -> usize {
@@ -311,6 +433,27 @@ impl EncodingScheme for TSL {
     fn dimension(&self) -> usize {
         self.config.v
     }
+
+    fn name(&self) -> &str {
+        "TSL"
+    }
+
+    /// The whole hash output is reduced modulo ℓ_{d₀}
+    fn entropy_bits_consumed(&self) -> usize {
+        self.hasher.output_size() * 8
+    }
+
+    /// Paper Construction 4: TSL maps only to layer d₀
+    fn support_layers(&self) -> RangeInclusive<usize> {
+        self.config.d0..=self.config.d0
+    }
+
+    fn layer_params(&self) -> LayerParams {
+        LayerParams {
+            d0: self.config.d0,
+            c: 0, // TSL has no checksum
+        }
+    }
 }
 
 impl NonUniformMapping for TSL {
@@ -343,6 +486,7 @@ mod tests {
     use super::*;
     use crate::core::hypercube::Hypercube;
     use crate::core::mapping;
+    use crate::crypto::hash::SHA256;
 
     #[test]
     fn test_tsl_config_creation() {
@@ -638,4 +782,278 @@ mod tests {
             assert_eq!(layer, d0);
         }
     }
+
+    #[test]
+    fn test_tsl_entropy_bits_consumed() {
+        let tsl = TSL::new(TSLConfig::new(128));
+
+        // The full SHA-256 output is reduced into the layer
+        assert_eq!(tsl.entropy_bits_consumed(), 256);
+
+        // Layer d0 is larger than 2^64 for paper-sized parameters, but the
+        // consumed entropy still covers it
+        assert!(tsl.layer_size.bits() > 64);
+        assert!(tsl.entropy_bits_consumed() >= tsl.layer_size.bits() as usize);
+    }
+
+    #[test]
+    fn test_tsl_map_to_layer_big() {
+        let tsl = TSL::new(TSLConfig::new(128));
+        assert!(tsl.layer_size > BigUint::from(u64::MAX));
+
+        let hc = Hypercube::new(tsl.config.w, tsl.config.v);
+        for i in 0..50u64 {
+            let message = i.to_be_bytes();
+            let vertex = TSL::encode(&tsl, &message, b"random seed").unwrap();
+            assert_eq!(hc.calculate_layer(&vertex), tsl.config.d0);
+        }
+
+        // Values beyond the layer size wrap around
+        let index = BigUint::from(12345u32);
+        assert_eq!(
+            tsl.map_to_layer_big(&(&tsl.layer_size + &index)).unwrap(),
+            tsl.map_to_layer_big(&index).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tsl_encode_integer() {
+        let tsl = TSL::new(TSLConfig::with_params(4, 4, 6));
+        let message = b"test message";
+        let randomness = b"random seed";
+
+        let value = tsl.encode_integer_big(message, randomness);
+        assert_eq!(value, tsl.encode_integer_big(message, randomness));
+        assert_ne!(value, tsl.encode_integer_big(b"other message", randomness));
+
+        // Mapping the intermediate integer reproduces encode
+        assert_eq!(
+            tsl.map_to_layer_big(&value).unwrap(),
+            tsl.encode(message, randomness).unwrap()
+        );
+
+        // The usize variant keeps the low 64 bits
+        let low = tsl.encode_integer(message, randomness);
+        assert_eq!(BigUint::from(low), value % (BigUint::from(u64::MAX) + 1u32));
+    }
+
+    #[test]
+    fn test_tsl_support_layers() {
+        let tsl = TSL::new(TSLConfig::with_params(4, 4, 6));
+        assert_eq!(tsl.support_layers(), 6..=6);
+    }
+
+    #[test]
+    fn test_tsl_coverage() {
+        let tsl = TSL::new(TSLConfig::with_params(4, 4, 6));
+        let layer_size = tsl.layer_size.to_usize().unwrap();
+
+        // Enough samples reach every vertex of the layer
+        assert_eq!(tsl.coverage(1000), layer_size);
+
+        // A degenerate mapping encodes no message at all
+        let degenerate = TSL {
+            config: TSLConfig::with_params(4, 4, 6),
+            hasher: HashAlg::Sha256,
+            layer_size: BigUint::zero(),
+        };
+        assert_eq!(degenerate.coverage(1000), 0);
+    }
+
+    #[test]
+    fn test_tsl_map_to_layer_no_fallback_to_index_zero() {
+        // Paper parameters: ℓ_{d₀} does not fit in usize
+        let tsl = TSL::new(TSLConfig::new(128));
+        let config = &tsl.config;
+        let zero_vertex =
+            integer_to_vertex_big(&BigUint::zero(), config.w, config.v, config.d0).unwrap();
+
+        let mut seen = HashSet::new();
+        for value in 1..200usize {
+            let vertex = tsl.map_to_layer(value).unwrap();
+            assert_ne!(vertex.components(), &zero_vertex);
+            assert!(seen.insert(vertex.components().clone()));
+        }
+
+        // Only indices congruent to 0 modulo a small layer reach its first vertex
+        let small = TSL::new(TSLConfig::with_params(4, 4, 6));
+        let layer_size = small.layer_size.to_usize().unwrap();
+        let first = small.map_to_layer(0).unwrap();
+        for value in 1..1000usize {
+            let vertex = small.map_to_layer(value).unwrap();
+            assert_eq!(vertex == first, value % layer_size == 0);
+        }
+    }
+
+    #[test]
+    fn test_tsl_max_message_bytes() {
+        let config = TSLConfig::with_params(4, 4, 6).with_max_message_bytes(16);
+        assert_eq!(config.max_message_bytes(), Some(16));
+        let tsl = TSL::new(config);
+
+        let randomness = b"random seed";
+        assert!(tsl.encode(&[0u8; 16], randomness).is_ok());
+        assert_eq!(
+            tsl.encode(&[0u8; 17], randomness),
+            Err(MappingError::MessageTooLong { len: 17, max: 16 })
+        );
+
+        // Long messages go through the prehashed API instead
+        let message_hash: [u8; 32] = SHA256::new().hash(&[0u8; 17]).try_into().unwrap();
+        assert!(tsl.encode_prehashed(&message_hash, randomness).is_ok());
+    }
+
+    #[test]
+    fn test_tsl_encode_uses_full_digest() {
+        let tsl = TSL::new(TSLConfig::new(160));
+
+        // Two digests agreeing on their first 8 bytes, as for a collision of
+        // the previous 64-bit truncation
+        let first = tsl.hasher.hash(b"first");
+        let mut second = tsl.hasher.hash(b"second");
+        second[..8].copy_from_slice(&first[..8]);
+
+        let a = tsl
+            .map_to_layer_big(&BigUint::from_bytes_le(&first))
+            .unwrap();
+        let b = tsl
+            .map_to_layer_big(&BigUint::from_bytes_le(&second))
+            .unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_tsl_with_blake3() {
+        let config = TSLConfig::with_params(4, 8, 12);
+        let sha256 = TSL::new(config.clone());
+        let blake3 = TSL::new(config.with_hash(HashAlg::Blake3));
+
+        let mut differs = false;
+        for i in 0..16u8 {
+            let message = [i; 8];
+            let vertex = blake3.encode(&message, b"randomness").unwrap();
+
+            assert_eq!(vertex, blake3.encode(&message, b"randomness").unwrap());
+            assert_eq!(Hypercube::new(4, 8).calculate_layer(&vertex), 12);
+            differs |= vertex != sha256.encode(&message, b"randomness").unwrap();
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn test_tsl_try_encode_never_returns_sink() {
+        let sink = Vertex::new(vec![4; 4]);
+        let hc = Hypercube::new(4, 4);
+
+        // Inputs that previously fell back to the sink: an empty layer and
+        // messages over the length limit
+        let degenerate = TSL {
+            config: TSLConfig::with_params(4, 4, 6),
+            hasher: HashAlg::Sha256,
+            layer_size: BigUint::zero(),
+        };
+        let limited = TSL::new(TSLConfig::with_params(4, 4, 6).with_max_message_bytes(4));
+        let tsl = TSL::new(TSLConfig::with_params(4, 4, 6));
+
+        for i in 0..100u64 {
+            let message = i.to_be_bytes();
+            assert!(matches!(
+                degenerate.try_encode(&message, b"randomness"),
+                Err(MappingError::EmptyLayer { .. })
+            ));
+            assert!(matches!(
+                limited.try_encode(&message, b"randomness"),
+                Err(MappingError::MessageTooLong { .. })
+            ));
+
+            let vertex = tsl.try_encode(&message, b"randomness").unwrap();
+            assert_ne!(vertex, sink);
+            assert_eq!(hc.calculate_layer(&vertex), 6);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "TSL encoding failed")]
+    fn test_tsl_encode_panics_instead_of_sink_fallback() {
+        let limited = TSL::new(TSLConfig::with_params(4, 4, 6).with_max_message_bytes(4));
+        <TSL as EncodingScheme>::encode(&limited, &[0u8; 8], b"randomness");
+    }
+
+    #[test]
+    fn test_tsl_paper_parameters_validate() {
+        for security_bits in [128, 160] {
+            let table = TSLConfig::paper_parameters(security_bits);
+            assert!(!table.is_empty());
+
+            for &(w, v, d0) in table {
+                let config = TSLConfig::with_params(w, v, d0).with_security_bits(security_bits);
+                assert_eq!(config.validate(), Ok(()), "{:?}", (w, v, d0));
+            }
+
+            // So does the config new builds from the tables
+            assert_eq!(TSLConfig::new(security_bits).validate(), Ok(()));
+        }
+        assert!(TSLConfig::paper_parameters(256).is_empty());
+
+        // new records the requested level
+        assert_eq!(TSLConfig::new(128).security_bits(), 128);
+        assert_eq!(TSLConfig::new(160).security_bits(), 160);
+    }
+
+    #[test]
+    fn test_tsl_validate() {
+        assert!(TSLConfig::with_params(86, 25, 1062).validate().is_ok());
+        assert!(TSLConfig::with_params(8, 64, 90).validate().is_ok());
+        let config = TSLConfig::with_params(86, 25, 1062).with_security_bits(160);
+        assert_eq!(config.security_bits(), 160);
+        assert_eq!(
+            config.validate(),
+            Err(ParamError::HypercubeTooSmall {
+                bits: 160,
+                required: 164
+            })
+        );
+
+        // [4]^4 holds only 2^8 vertices
+        let weak = TSLConfig::with_params(4, 4, 2);
+        assert_eq!(
+            weak.validate(),
+            Err(ParamError::HypercubeTooSmall {
+                bits: 8,
+                required: 132
+            })
+        );
+
+        // A large enough hypercube with a layer well short of 2^128
+        assert_eq!(
+            TSLConfig::with_params(86, 25, 200).validate(),
+            Err(ParamError::LayerTooSmall {
+                bits: 106,
+                required: 128
+            })
+        );
+
+        // The (4, 132, 39) entry: ℓ_39 ≈ 2^127.8 rounds to λ, ℓ_38 does not
+        assert!(TSLConfig::with_params(4, 132, 39).validate().is_ok());
+        assert!(matches!(
+            TSLConfig::with_params(4, 132, 38).validate(),
+            Err(ParamError::LayerTooSmall { required: 128, .. })
+        ));
+    }
+
+    #[test]
+    fn test_tsl_find_fallback_input() {
+        // Every 8-byte probe exceeds the limit
+        let limited = TSL::new(TSLConfig::with_params(4, 4, 6).with_max_message_bytes(4));
+        let (message, randomness) = limited.find_fallback_input(10).unwrap();
+        assert!(TSL::encode(&limited, &message, &randomness).is_err());
+        assert_eq!(
+            limited.try_encode(&message, &randomness),
+            Err(MappingError::MessageTooLong { len: 8, max: 4 })
+        );
+
+        // A healthy encoder never falls back
+        let tsl = TSL::new(TSLConfig::with_params(4, 4, 6));
+        assert_eq!(tsl.find_fallback_input(100), None);
+    }
 }
@@ -0,0 +1,23 @@
+// Scheme-independent signing interface
+//
+// These traits let downstream code be generic over the signature scheme.
+// The message type is associated so that WOTS, which signs digit vectors,
+// and XMSS, which signs byte strings, share the same interface.
+
+/// A key that can produce signatures
+pub trait Signer {
+    type Message: ?Sized;
+    type Signature;
+    type Error;
+
+    /// Sign a message, advancing any internal state (e.g. the XMSS leaf index)
+    fn sign(&mut self, message: &Self::Message) -> Result<Self::Signature, Self::Error>;
+}
+
+/// A key that can check signatures
+pub trait Verifier {
+    type Message: ?Sized;
+    type Signature;
+
+    fn verify(&self, message: &Self::Message, signature: &Self::Signature) -> bool;
+}
@@ -0,0 +1,22 @@
+use crate::core::mapping::MappingError;
+use thiserror::Error;
+
+/// Errors returned by fallible WOTS operations
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum WotsError {
+    #[error("WOTS message digit {value} at index {index} out of range [0, {w})")]
+    DigitOutOfRange {
+        index: usize,
+        value: usize,
+        w: usize,
+    },
+
+    #[error("WOTS message digest length {actual} does not match {expected} chains")]
+    LengthMismatch { expected: usize, actual: usize },
+
+    #[error("WOTS key already used to sign")]
+    AlreadyUsed,
+
+    #[error("message cannot be encoded: {0:?}")]
+    Encoding(MappingError),
+}
@@ -0,0 +1,116 @@
+// Chain-level comparison of two WOTS digests
+//
+// A signature on a digest reveals H^{xᵢ}(skᵢ); anyone can hash forward to any
+// digit above xᵢ, but going back requires the secret. A second digest is thus
+// forgeable from the first exactly when no digit decreases, which is what a
+// checksum moving opposite to the data prevents.
+
+use crate::wots::{check_digest, WotsError, WotsParams};
+
+/// Per-chain movement from a signed digest to a target digest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeryAnalysis {
+    /// Chains whose digit increases: reachable by hashing forward
+    forward: Vec<usize>,
+    /// Chains whose digit decreases: need the secret key
+    backward: Vec<usize>,
+}
+
+impl ForgeryAnalysis {
+    pub fn forward(&self) -> &[usize] {
+        &self.forward
+    }
+
+    pub fn backward(&self) -> &[usize] {
+        &self.backward
+    }
+
+    /// Whether a signature on the first digest yields one on the second
+    /// True when the digests differ and no chain moves backward, i.e. the
+    /// digit vectors lack the checksum protection against forward forgery.
+    pub fn is_forgeable(&self) -> bool {
+        !self.forward.is_empty() && self.backward.is_empty()
+    }
+}
+
+/// Compare the digits of a signed digest `d1` with a target digest `d2`
+/// Panics if the digests have different lengths.
+pub fn forgery_delta(d1: &[usize], d2: &[usize]) -> ForgeryAnalysis {
+    assert_eq!(d1.len(), d2.len(), "Digests must have the same length");
+
+    let mut forward = Vec::new();
+    let mut backward = Vec::new();
+
+    for (i, (&x1, &x2)) in d1.iter().zip(d2.iter()).enumerate() {
+        if x2 > x1 {
+            forward.push(i);
+        } else if x2 < x1 {
+            backward.push(i);
+        }
+    }
+
+    ForgeryAnalysis { forward, backward }
+}
+
+/// Fallible `forgery_delta` for digests signed under `params`
+/// Rejects digests that do not have one digit in [0, w-1] per chain.
+pub fn try_forgery_delta(
+    d1: &[usize],
+    d2: &[usize],
+    params: &WotsParams,
+) -> Result<ForgeryAnalysis, WotsError> {
+    check_digest(d1, params)?;
+    check_digest(d2, params)?;
+    Ok(forgery_delta(d1, d2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forgery_delta_without_checksum() {
+        let analysis = forgery_delta(&[0; 8], &[1; 8]);
+
+        assert_eq!(analysis.forward(), (0..8).collect::<Vec<_>>().as_slice());
+        assert!(analysis.backward().is_empty());
+        assert!(analysis.is_forgeable());
+    }
+
+    #[test]
+    fn test_forgery_delta_with_checksum() {
+        // Two data digits for w = 4 followed by the checksum Σ(w-1-xᵢ) in
+        // base 4: data (0, 0) has checksum 6 = (1, 2), data (1, 1) has 4 = (1, 0).
+        // Raising the data digits lowers a checksum digit
+        let params = WotsParams::new(4, 4);
+        let analysis = try_forgery_delta(&[0, 0, 1, 2], &[1, 1, 1, 0], &params).unwrap();
+
+        assert_eq!(analysis.forward(), &[0, 1]);
+        assert_eq!(analysis.backward(), &[3]);
+        assert!(!analysis.is_forgeable());
+
+        // Identical digests are not a forgery
+        assert!(!forgery_delta(&[1, 2], &[1, 2]).is_forgeable());
+    }
+
+    #[test]
+    fn test_try_forgery_delta_rejects_out_of_range_digits() {
+        // The unencoded checksum 6 is not a digit for w = 4
+        let params = WotsParams::new(4, 3);
+        assert_eq!(
+            try_forgery_delta(&[0, 0, 6], &[1, 1, 3], &params),
+            Err(WotsError::DigitOutOfRange {
+                index: 2,
+                value: 6,
+                w: 4
+            })
+        );
+        assert_eq!(
+            try_forgery_delta(&[0, 0, 3], &[1, 1], &params),
+            Err(WotsError::LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+}
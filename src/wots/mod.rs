@@ -3,22 +3,64 @@
 // This module implements the standard WOTS signature scheme that is
 // integrated with the hypercube-based encoding schemes.
 
-use crate::crypto::hash::{HashFunction, SHA256};
+pub mod error;
+pub mod forgery;
+
+pub use self::error::WotsError;
+pub use self::forgery::{forgery_delta, try_forgery_delta, ForgeryAnalysis};
+
+use crate::crypto::hash::{HashAlg, HashFunction};
 use crate::crypto::random::{OsSecureRandom, SecureRandom};
+use crate::schemes::tl1c::TL1CConfig;
+use crate::schemes::tlfc::TLFCConfig;
+use crate::schemes::tsl::TSLConfig;
+use crate::signer::{Signer, Verifier};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 
 /// WOTS parameters
 /// WOTS parameters derived from hypercube scheme
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WotsParams {
     w: usize,
     chains: usize,
+    hash: HashAlg,
 }
 
 impl WotsParams {
     pub fn new(w: usize, chains: usize) -> Self {
         assert!(w > 1, "w must be greater than 1");
         assert!(chains > 0, "chains must be positive");
-        WotsParams { w, chains }
+        WotsParams {
+            w,
+            chains,
+            hash: HashAlg::default(),
+        }
+    }
+
+    /// WOTS parameters for signing TSL digests: v chains over [w]
+    pub fn from_tsl(config: &TSLConfig) -> Self {
+        WotsParams::new(config.w(), config.signature_chains()).with_hash(config.hash())
+    }
+
+    /// WOTS parameters for signing TL1C digests: v + 1 chains over [w]
+    pub fn from_tl1c(config: &TL1CConfig) -> Self {
+        WotsParams::new(config.w(), config.signature_chains()).with_hash(config.hash())
+    }
+
+    /// WOTS parameters for signing TLFC digests: v + c chains over [w]
+    pub fn from_tlfc(config: &TLFCConfig) -> Self {
+        WotsParams::new(config.w(), config.signature_chains()).with_hash(config.hash())
+    }
+
+    /// Select the chain hash function (SHA-256 by default)
+    /// Keygen, signing and verification all use the hash stored in the params.
+    pub fn with_hash(mut self, hash: HashAlg) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    pub fn hash(&self) -> HashAlg {
+        self.hash
     }
 
     pub fn w(&self) -> usize {
@@ -36,7 +78,7 @@ impl WotsParams {
 
 /// WOTS public key
 /// pk = (pk₁, ..., pkₗ) where pkᵢ = H^{w-1}(skᵢ)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WotsPublicKey {
     chains: Vec<Vec<u8>>,
     params: WotsParams,
@@ -59,35 +101,104 @@ impl WotsPublicKey {
     /// Paper Algorithm WOTS-Verify: Verifies signature by checking
     /// if H^{w-1-xᵢ}(σᵢ) = pkᵢ for all i
     pub fn verify(&self, message_digest: &[usize], signature: &WotsSignature) -> bool {
-        if message_digest.len() != self.params.chains {
-            return false;
+        self.verify_profiled(message_digest, signature).0
+    }
+
+    /// Verify a signature produced by `WotsKeypair::sign` with the same encoding
+    pub fn verify_message<E: crate::core::encoding::EncodingScheme>(
+        &self,
+        message: &[u8],
+        encoding: &E,
+        signature: &WotsSignature,
+    ) -> bool {
+        let randomness = [0u8; 32];
+        match encoding.encode_to_digest(message, &randomness) {
+            Ok(message_digest) => self.verify(&message_digest, signature),
+            // A message the encoding rejects has no valid signature
+            Err(_) => false,
         }
+    }
 
-        if signature.chains.len() != self.params.chains {
-            return false;
+    /// Verify a signature and report the work performed
+    /// Chains with xᵢ = w-1 need zero iterations (σᵢ = pkᵢ). Every chain is
+    /// hashed and compared in constant time, with the results folded into one
+    /// validity bit, so the work does not depend on where a mismatch occurs.
+    pub fn verify_profiled(
+        &self,
+        message_digest: &[usize],
+        signature: &WotsSignature,
+    ) -> (bool, VerifyProfile) {
+        let mut profile = VerifyProfile::default();
+
+        if message_digest.len() != self.params.chains
+            || signature.chains.len() != self.params.chains
+            || message_digest.iter().any(|&x_i| x_i >= self.params.w)
+        {
+            return (false, profile);
         }
 
-        // Check each chain
-        let hasher = SHA256::new();
-        for i in 0..self.params.chains {
-            let x_i = message_digest[i];
-            if x_i >= self.params.w {
-                return false;
-            }
+        let max_digit = self.params.w - 1;
+        let hasher = self.params.hash;
+        let mut valid = Choice::from(1);
 
-            // Compute H^{w-1-xᵢ}(σᵢ) and check if it equals pkᵢ
-            let iterations = self.params.w - 1 - x_i;
-            let computed = hash_chain(&hasher, &signature.chains[i], iterations);
+        // Compute H^{w-1-xᵢ}(σᵢ) and check if it equals pkᵢ
+        for ((&x_i, sig_i), pk_i) in message_digest
+            .iter()
+            .zip(signature.chains.iter())
+            .zip(self.chains.iter())
+        {
+            let iterations = max_digit - x_i;
+            let computed = hash_chain(&hasher, sig_i, iterations);
+            profile.chains_checked += 1;
+            profile.hash_iterations += iterations;
+
+            valid &= computed.as_slice().ct_eq(pk_i.as_slice());
+        }
 
-            if computed != self.chains[i] {
-                return false;
-            }
+        (valid.into(), profile)
+    }
+
+    /// DIAGNOSTICS ONLY: accept a signature with at most `max_bad` bad chains
+    ///
+    /// This is NOT a signature check. A forger who knows all but `max_bad`
+    /// chains passes it, so it must never be used to accept signatures. It
+    /// exists to measure how many chains a transmission error corrupted.
+    /// Malformed digests and chain count mismatches are rejected outright.
+    pub fn verify_tolerant(
+        &self,
+        message_digest: &[usize],
+        signature: &WotsSignature,
+        max_bad: usize,
+    ) -> bool {
+        if check_digest(message_digest, &self.params).is_err()
+            || signature.chains.len() != self.params.chains
+        {
+            return false;
         }
 
-        true
+        let hasher = self.params.hash;
+        let bad_chains = message_digest
+            .iter()
+            .zip(signature.chains.iter())
+            .zip(self.chains.iter())
+            .filter(|((&x_i, sig_i), pk_i)| {
+                !ct_eq(&hash_chain(&hasher, sig_i, self.params.w - 1 - x_i), pk_i)
+            })
+            .count();
+
+        bad_chains <= max_bad
     }
 }
 
+/// Work performed by `WotsPublicKey::verify_profiled`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerifyProfile {
+    /// Number of chains compared against the public key
+    pub chains_checked: usize,
+    /// Total number of hash evaluations across all chains
+    pub hash_iterations: usize,
+}
+
 /// WOTS secret key
 /// sk = (sk₁, ..., skₗ) where each skᵢ is random
 #[derive(Debug, Clone)]
@@ -105,6 +216,26 @@ impl WotsSecretKey {
     }
 }
 
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for WotsSecretKey {
+    fn zeroize(&mut self) {
+        // Chains are wiped in place, keeping their length and allocation
+        for chain in self.chains.iter_mut() {
+            chain.as_mut_slice().zeroize();
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for WotsSecretKey {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for WotsSecretKey {}
+
 /// WOTS keypair
 pub struct WotsKeypair {
     public_key: WotsPublicKey,
@@ -115,8 +246,13 @@ pub struct WotsKeypair {
 impl WotsKeypair {
     /// Generate a new keypair
     pub fn generate(params: &WotsParams) -> Self {
-        let mut rng = OsSecureRandom::new();
-        let hasher = SHA256::new();
+        Self::generate_with_rng(params, &mut OsSecureRandom::new())
+    }
+
+    /// Generate a keypair drawing the secret chains from `rng`
+    /// With a seeded generator such as `ChaChaRandom` the keypair is reproducible.
+    pub fn generate_with_rng<R: SecureRandom + ?Sized>(params: &WotsParams, rng: &mut R) -> Self {
+        let hasher = params.hash;
 
         let mut sk_chains = Vec::with_capacity(params.chains);
         let mut pk_chains = Vec::with_capacity(params.chains);
@@ -163,60 +299,128 @@ impl WotsKeypair {
         &self.secret_key
     }
 
+    /// Self-contained verifying key carrying this keypair's parameters
+    pub fn export_public(&self) -> WotsPublicKey {
+        WotsPublicKey {
+            chains: self.public_key.chains.clone(),
+            params: self.params.clone(),
+        }
+    }
+
     /// Sign a message with encoding
     /// Integration with hypercube encoding
     /// The encoding scheme maps the message to a vertex which provides
-    /// the WOTS message digits
+    /// the WOTS message digits. Fails if the encoding rejects the message.
     pub fn sign<E: crate::core::encoding::EncodingScheme>(
         &self,
         message: &[u8],
         encoding: &E,
-    ) -> WotsSignature {
+    ) -> Result<WotsSignature, WotsError> {
         // For deterministic encoding, use zeros as randomness
         // The message itself provides the entropy
         let randomness = [0u8; 32];
 
-        // Encode message to hypercube vertex
-        let vertex = encoding.encode(message, &randomness);
+        // The encoding yields the WOTS message digits, including any checksum
+        let message_digest = encoding
+            .encode_to_digest(message, &randomness)
+            .map_err(WotsError::Encoding)?;
 
-        // The vertex components (a₁, ..., aᵥ) become WOTS message digits
-        // Convert from hypercube range [1, w] to WOTS range [0, w-1]
-        let message_digest: Vec<usize> = vertex
-            .components()
-            .iter()
-            .map(|&x| x.saturating_sub(1))
-            .collect();
-
-        self.sign_raw(&message_digest)
+        self.try_sign_raw(&message_digest)
     }
 
     /// Sign a message digest
     /// σᵢ = H^{xᵢ}(skᵢ) for each digit xᵢ
+    /// Panics on a malformed digest; see `try_sign_raw`.
     pub fn sign_raw(&self, message_digest: &[usize]) -> WotsSignature {
-        assert_eq!(
-            message_digest.len(),
-            self.params.chains,
-            "Message digest length must match number of chains"
-        );
+        match self.try_sign_raw(message_digest) {
+            Ok(signature) => signature,
+            Err(e) => panic!("{}", e),
+        }
+    }
 
-        let hasher = SHA256::new();
-        let mut sig_chains = Vec::with_capacity(self.params.chains);
-
-        for i in 0..self.params.chains {
-            let x_i = message_digest[i];
-            assert!(
-                x_i < self.params.w,
-                "Message digit {} out of range [0, {})",
-                x_i,
-                self.params.w
-            );
+    /// Sign a message digest, rejecting digests of the wrong length or with
+    /// digits outside [0, w-1]
+    pub fn try_sign_raw(&self, message_digest: &[usize]) -> Result<WotsSignature, WotsError> {
+        check_digest(message_digest, &self.params)?;
+
+        let hasher = self.params.hash;
+        let sig_chains = message_digest
+            .iter()
+            .zip(self.secret_key.chains.iter())
+            .map(|(&x_i, sk_i)| {
+                // Compute σᵢ = H^{xᵢ}(skᵢ) in time independent of xᵢ
+                hash_chain_fixed(&hasher, sk_i, x_i, self.params.w - 1)
+            })
+            .collect();
+
+        Ok(WotsSignature { chains: sig_chains })
+    }
+}
+
+/// WOTS signs a digest of digits in [0, w-1]; use an encoding scheme to obtain one
+impl Signer for WotsKeypair {
+    type Message = [usize];
+    type Signature = WotsSignature;
+    type Error = WotsError;
+
+    fn sign(&mut self, message_digest: &[usize]) -> Result<WotsSignature, WotsError> {
+        self.try_sign_raw(message_digest)
+    }
+}
+
+/// WOTS keypair that refuses to sign twice
+/// Signing two digests with one WOTS key reveals enough chain values to forge
+/// others; the wrapper enforces single use for keys outside an XMSS tree.
+pub struct OneTimeWots {
+    keypair: WotsKeypair,
+    used: bool,
+}
+
+impl OneTimeWots {
+    pub fn new(keypair: WotsKeypair) -> Self {
+        OneTimeWots {
+            keypair,
+            used: false,
+        }
+    }
+
+    pub fn public_key(&self) -> &WotsPublicKey {
+        self.keypair.public_key()
+    }
+
+    pub fn is_used(&self) -> bool {
+        self.used
+    }
 
-            // Compute σᵢ = H^{xᵢ}(skᵢ)
-            let sig_i = hash_chain(&hasher, &self.secret_key.chains[i], x_i);
-            sig_chains.push(sig_i);
+    /// Sign a digest, failing with `AlreadyUsed` after the first signature
+    /// A malformed digest is rejected without consuming the key.
+    pub fn sign(&mut self, message_digest: &[usize]) -> Result<WotsSignature, WotsError> {
+        if self.used {
+            return Err(WotsError::AlreadyUsed);
         }
 
-        WotsSignature { chains: sig_chains }
+        let signature = self.keypair.try_sign_raw(message_digest)?;
+        self.used = true;
+        Ok(signature)
+    }
+}
+
+impl Signer for OneTimeWots {
+    type Message = [usize];
+    type Signature = WotsSignature;
+    type Error = WotsError;
+
+    fn sign(&mut self, message_digest: &[usize]) -> Result<WotsSignature, WotsError> {
+        OneTimeWots::sign(self, message_digest)
+    }
+}
+
+impl Verifier for WotsPublicKey {
+    type Message = [usize];
+    type Signature = WotsSignature;
+
+    fn verify(&self, message_digest: &[usize], signature: &WotsSignature) -> bool {
+        WotsPublicKey::verify(self, message_digest, signature)
     }
 }
 
@@ -235,6 +439,68 @@ impl WotsSignature {
     pub fn from_chains(chains: Vec<Vec<u8>>) -> Self {
         WotsSignature { chains }
     }
+
+    /// Recover the public key this signature was made under
+    /// pkᵢ = H^{w-1-xᵢ}(σᵢ); the result equals the signer's public key exactly
+    /// when the signature is valid for `message_digest`.
+    /// Panics on a malformed digest or a signature with the wrong chain count.
+    pub fn recover_public_key(
+        &self,
+        message_digest: &[usize],
+        params: &WotsParams,
+    ) -> WotsPublicKey {
+        if let Err(e) = check_digest(message_digest, params) {
+            panic!("{}", e);
+        }
+        if self.chains.len() != params.chains {
+            panic!(
+                "{}",
+                WotsError::LengthMismatch {
+                    expected: params.chains,
+                    actual: self.chains.len(),
+                }
+            );
+        }
+
+        let max_digit = params.w - 1;
+        let chains = message_digest
+            .iter()
+            .zip(self.chains.iter())
+            .map(|(&x_i, sigma_i)| hash_chain(&params.hash, sigma_i, max_digit - x_i))
+            .collect();
+
+        WotsPublicKey::from_chains(chains, params.clone())
+    }
+}
+
+/// Check that a digest has one digit in [0, w-1] per chain
+pub(crate) fn check_digest(message_digest: &[usize], params: &WotsParams) -> Result<(), WotsError> {
+    if message_digest.len() != params.chains {
+        return Err(WotsError::LengthMismatch {
+            expected: params.chains,
+            actual: message_digest.len(),
+        });
+    }
+
+    if let Some((index, &value)) = message_digest
+        .iter()
+        .enumerate()
+        .find(|(_, &x_i)| x_i >= params.w)
+    {
+        return Err(WotsError::DigitOutOfRange {
+            index,
+            value,
+            w: params.w,
+        });
+    }
+
+    Ok(())
+}
+
+/// Compare byte strings in time independent of their contents
+/// Slices of different lengths compare unequal; lengths are not secret.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
 }
 
 /// Compute hash chain H^k(x)
@@ -252,9 +518,45 @@ pub fn hash_chain(hasher: &dyn HashFunction, input: &[u8], iterations: usize) ->
     result
 }
 
+/// Compute H^k(x) in time independent of k
+/// Always applies `max_iterations` hashes and keeps the k-th intermediate
+/// value through a constant-time select, so the running time does not reveal
+/// a secret-dependent k. The input must be one hash output long.
+pub fn hash_chain_fixed(
+    hasher: &dyn HashFunction,
+    input: &[u8],
+    iterations: usize,
+    max_iterations: usize,
+) -> Vec<u8> {
+    assert!(
+        iterations <= max_iterations,
+        "iterations must not exceed max_iterations"
+    );
+    assert_eq!(
+        input.len(),
+        hasher.output_size(),
+        "hash chain input must be one hash output long"
+    );
+
+    let target = iterations as u64;
+    let mut result = input.to_vec();
+    let mut current = input.to_vec();
+
+    for i in 1..=max_iterations {
+        current = hasher.hash(&current);
+        let keep = (i as u64).ct_eq(&target);
+        for (r, c) in result.iter_mut().zip(current.iter()) {
+            r.conditional_assign(c, keep);
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::hash::SHA256;
 
     #[test]
     fn test_wots_params() {
@@ -266,6 +568,23 @@ mod tests {
         assert_eq!(params.max_hash_iterations(), 3); // w-1 = 3
     }
 
+    #[test]
+    fn test_wots_params_from_scheme_configs() {
+        let tsl = TSLConfig::with_params(5, 3, 3);
+        let params = WotsParams::from_tsl(&tsl);
+        assert_eq!((params.w(), params.chains()), (5, 3));
+
+        let tl1c = TL1CConfig::with_params(5, 3, 3);
+        let params = WotsParams::from_tl1c(&tl1c);
+        assert_eq!((params.w(), params.chains()), (5, 4));
+
+        let tlfc = TLFCConfig::with_params(8, 4, 3, 2).with_hash(HashAlg::Blake3);
+        let params = WotsParams::from_tlfc(&tlfc);
+        assert_eq!(params.w(), tlfc.w());
+        assert_eq!(params.chains(), tlfc.v() + tlfc.c());
+        assert_eq!(params.hash(), HashAlg::Blake3);
+    }
+
     #[test]
     fn test_wots_keygen() {
         let params = WotsParams::new(4, 8); // Small params for testing
@@ -283,6 +602,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wots_keygen_with_seeded_rng() {
+        use crate::crypto::random::ChaChaRandom;
+
+        let params = WotsParams::new(4, 8);
+        let a = WotsKeypair::generate_with_rng(&params, &mut ChaChaRandom::from_seed([7u8; 32]));
+        let b = WotsKeypair::generate_with_rng(&params, &mut ChaChaRandom::from_seed([7u8; 32]));
+        assert_eq!(a.secret_key().chains(), b.secret_key().chains());
+        assert_eq!(a.public_key(), b.public_key());
+
+        let c = WotsKeypair::generate_with_rng(&params, &mut ChaChaRandom::from_seed([8u8; 32]));
+        assert_ne!(a.public_key(), c.public_key());
+    }
+
+    #[test]
+    fn test_wots_keygen_os_random_differs() {
+        let params = WotsParams::new(4, 8);
+        let a = WotsKeypair::generate(&params);
+        let b = WotsKeypair::generate(&params);
+        assert_ne!(a.secret_key().chains(), b.secret_key().chains());
+        assert_ne!(a.public_key(), b.public_key());
+    }
+
     #[test]
     fn test_wots_sign_verify() {
         let params = WotsParams::new(4, 8);
@@ -312,6 +654,38 @@ mod tests {
         assert!(!keypair.public_key().verify(&wrong_message, &signature));
     }
 
+    #[test]
+    fn test_export_public() {
+        let params = WotsParams::new(4, 8).with_hash(HashAlg::Sha512);
+        let keypair = WotsKeypair::generate(&params);
+        let message_digest = vec![1, 2, 0, 3, 1, 2, 0, 3];
+        let signature = keypair.sign_raw(&message_digest);
+
+        let exported = keypair.export_public();
+        assert_eq!(exported.params(), &params);
+        assert_eq!(&exported, keypair.public_key());
+        assert!(exported.verify(&message_digest, &signature));
+    }
+
+    #[test]
+    fn test_verify_tolerant_counts_bad_chains() {
+        let params = WotsParams::new(4, 8);
+        let keypair = WotsKeypair::generate(&params);
+        let message_digest = vec![1, 2, 0, 3, 1, 2, 0, 3];
+        let signature = keypair.sign_raw(&message_digest);
+
+        let mut chains = signature.chains().to_vec();
+        chains[0][0] ^= 0x01;
+        chains[5][0] ^= 0x01;
+        let corrupted = WotsSignature::from_chains(chains);
+
+        let public_key = keypair.public_key();
+        assert!(!public_key.verify(&message_digest, &corrupted));
+        assert!(public_key.verify_tolerant(&message_digest, &corrupted, 2));
+        assert!(!public_key.verify_tolerant(&message_digest, &corrupted, 1));
+        assert!(public_key.verify_tolerant(&message_digest, &signature, 0));
+    }
+
     #[test]
     fn test_wots_signature_size() {
         let params = WotsParams::new(4, 64);
@@ -386,4 +760,184 @@ mod tests {
         // But signing two messages reveals information
         // (In practice, this key should never be used again)
     }
+
+    #[test]
+    fn test_wots_verify_profile() {
+        let params = WotsParams::new(4, 4);
+        let keypair = WotsKeypair::generate(&params);
+
+        // All digits w-1: every chain is compared without hashing
+        let max_digest = vec![3; 4];
+        let signature = keypair.sign_raw(&max_digest);
+        let (valid, profile) = keypair
+            .public_key()
+            .verify_profiled(&max_digest, &signature);
+        assert!(valid);
+        assert_eq!(profile.hash_iterations, 0);
+        assert_eq!(profile.chains_checked, 4);
+
+        // Mixed digits cost Σ(w-1-xᵢ) hash evaluations
+        let digest = vec![0, 1, 2, 3];
+        let signature = keypair.sign_raw(&digest);
+        let (valid, profile) = keypair.public_key().verify_profiled(&digest, &signature);
+        assert!(valid);
+        assert_eq!(profile.hash_iterations, 3 + 2 + 1);
+
+        // A mismatch costs the same work wherever it occurs
+        for bad in 0..4 {
+            let mut forged = signature.chains().to_vec();
+            forged[bad] = vec![0u8; 32];
+            let (valid, profile) = keypair
+                .public_key()
+                .verify_profiled(&digest, &WotsSignature::from_chains(forged));
+            assert!(!valid);
+            assert_eq!(profile.chains_checked, 4);
+            assert_eq!(profile.hash_iterations, 3 + 2 + 1);
+        }
+    }
+
+    #[test]
+    fn test_ct_eq_matches_eq() {
+        let a = [1u8, 2, 3, 4];
+        let cases: [&[u8]; 5] = [
+            &[1, 2, 3, 4],
+            &[0, 2, 3, 4],
+            &[1, 2, 3, 5],
+            &[4, 3, 2, 1],
+            &[1, 2, 3],
+        ];
+
+        for b in cases {
+            assert_eq!(ct_eq(&a, b), a.as_slice() == b);
+        }
+        assert!(ct_eq(&[], &[]));
+    }
+
+    #[test]
+    fn test_wots_try_sign_raw_errors() {
+        let params = WotsParams::new(4, 4);
+        let keypair = WotsKeypair::generate(&params);
+
+        assert_eq!(
+            keypair.try_sign_raw(&[0, 1, 4, 2]).unwrap_err(),
+            WotsError::DigitOutOfRange {
+                index: 2,
+                value: 4,
+                w: 4
+            }
+        );
+
+        assert_eq!(
+            keypair.try_sign_raw(&[0, 1, 2]).unwrap_err(),
+            WotsError::LengthMismatch {
+                expected: 4,
+                actual: 3
+            }
+        );
+
+        let digest = [0, 1, 2, 3];
+        let signature = keypair.try_sign_raw(&digest).unwrap();
+        assert!(keypair.public_key().verify(&digest, &signature));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_wots_secret_key_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut secret_key = WotsSecretKey::from_chains(vec![vec![0x42; 32], vec![0x24; 32]]);
+
+        // Drop runs exactly this; the wiped chains are still owned by the key
+        secret_key.zeroize();
+        assert!(secret_key
+            .chains()
+            .iter()
+            .all(|chain| chain.len() == 32 && chain.iter().all(|&b| b == 0)));
+    }
+
+    #[test]
+    fn test_wots_selectable_hash() {
+        let digest = vec![1, 2, 0, 3, 1, 2, 0, 3];
+
+        for (hash, size) in [
+            (HashAlg::Sha256, 32),
+            (HashAlg::Sha512, 64),
+            (HashAlg::Shake256, 32),
+        ] {
+            let params = WotsParams::new(4, 8).with_hash(hash);
+            let keypair = WotsKeypair::generate(&params);
+            let signature = keypair.sign_raw(&digest);
+
+            assert!(keypair
+                .public_key()
+                .chains()
+                .iter()
+                .all(|c| c.len() == size));
+            assert!(signature.chains().iter().all(|c| c.len() == size));
+            assert!(keypair.public_key().verify(&digest, &signature));
+        }
+
+        // A SHA-512 signature does not verify under a SHA-256 key of the same secret
+        let sha512 = WotsKeypair::generate(&WotsParams::new(4, 8).with_hash(HashAlg::Sha512));
+        let sha256_pk = WotsPublicKey::from_chains(
+            sha512.public_key().chains().to_vec(),
+            WotsParams::new(4, 8),
+        );
+        let signature = sha512.sign_raw(&digest);
+        assert!(!sha256_pk.verify(&digest, &signature));
+    }
+
+    #[test]
+    fn test_wots_recover_public_key() {
+        let params = WotsParams::new(16, 10);
+        let keypair = WotsKeypair::generate(&params);
+        let digest = vec![0, 15, 3, 7, 1, 14, 8, 2, 9, 5];
+
+        let signature = keypair.sign_raw(&digest);
+        assert_eq!(
+            &signature.recover_public_key(&digest, &params),
+            keypair.public_key()
+        );
+
+        // A different digest recovers a different key
+        let mut other = digest.clone();
+        other[0] = 1;
+        assert_ne!(
+            &signature.recover_public_key(&other, &params),
+            keypair.public_key()
+        );
+    }
+
+    #[test]
+    fn test_hash_chain_fixed_matches_hash_chain() {
+        let hasher = SHA256::new();
+        let input = hasher.hash(b"chain start");
+        let max = 15;
+
+        for k in 0..=max {
+            assert_eq!(
+                hash_chain_fixed(&hasher, &input, k, max),
+                hash_chain(&hasher, &input, k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_one_time_wots_rejects_second_sign() {
+        let params = WotsParams::new(4, 4);
+        let mut wots = OneTimeWots::new(WotsKeypair::generate(&params));
+
+        // A malformed digest does not consume the key
+        assert!(wots.sign(&[0, 1, 2]).is_err());
+        assert!(!wots.is_used());
+
+        let signature = wots.sign(&[0, 1, 2, 3]).unwrap();
+        assert!(wots.public_key().verify(&[0, 1, 2, 3], &signature));
+        assert!(wots.is_used());
+
+        assert_eq!(
+            wots.sign(&[3, 2, 1, 0]).unwrap_err(),
+            WotsError::AlreadyUsed
+        );
+    }
 }
@@ -0,0 +1,271 @@
+// Compact encoding of signatures made at consecutive leaves
+//
+// Signatures from one key at leaves i, i+1, ..., i+n-1 share upper
+// authentication path nodes. A batch stores the leaf index once and the
+// nodes of a single multi-proof instead of n full paths.
+
+use crate::wots::WotsSignature;
+use crate::xmss::core::{XMSSParams, XMSSPublicKey};
+use crate::xmss::error::{XMSSSignatureError, XmssError};
+use crate::xmss::signature::XMSSSignature;
+use crate::xmss::tree::{AuthPath, MultiProof};
+
+#[derive(Debug, Clone)]
+pub struct XmssSignatureBatch {
+    first_index: usize,
+    randomness: Vec<Vec<u8>>,
+    wots_signatures: Vec<WotsSignature>,
+    proof: MultiProof,
+}
+
+impl XmssSignatureBatch {
+    /// Pack signatures with consecutive leaf indices, in index order
+    pub fn new(signatures: &[XMSSSignature]) -> Result<Self, XmssError> {
+        let first_index = signatures
+            .first()
+            .ok_or(XmssError::InvalidBatch)?
+            .leaf_index();
+
+        if signatures
+            .iter()
+            .enumerate()
+            .any(|(i, sig)| sig.leaf_index() != first_index + i)
+        {
+            return Err(XmssError::InvalidBatch);
+        }
+
+        let indices: Vec<usize> = signatures.iter().map(|sig| sig.leaf_index()).collect();
+        let paths: Vec<AuthPath> = signatures
+            .iter()
+            .map(|sig| sig.auth_path().clone())
+            .collect();
+        let proof = MultiProof::from_auth_paths(&indices, &paths).ok_or(XmssError::InvalidBatch)?;
+
+        Ok(XmssSignatureBatch {
+            first_index,
+            randomness: signatures
+                .iter()
+                .map(|sig| sig.randomness().to_vec())
+                .collect(),
+            wots_signatures: signatures
+                .iter()
+                .map(|sig| sig.wots_signature().clone())
+                .collect(),
+            proof,
+        })
+    }
+
+    pub fn first_index(&self) -> usize {
+        self.first_index
+    }
+
+    pub fn len(&self) -> usize {
+        self.randomness.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.randomness.is_empty()
+    }
+
+    /// Verify `messages[i]` against the signature at leaf `first_index + i`
+    pub fn verify(
+        &self,
+        messages: &[&[u8]],
+        public_key: &XMSSPublicKey,
+        params: &XMSSParams,
+    ) -> bool {
+        if messages.len() != self.len() {
+            return false;
+        }
+
+        let indices: Vec<usize> = (self.first_index..self.first_index + self.len()).collect();
+        let mut leaves = Vec::with_capacity(self.len());
+
+        for (i, message) in messages.iter().enumerate() {
+            // The leaf does not depend on the authentication path
+            let signature = XMSSSignature::new(
+                indices[i],
+                self.randomness[i].clone(),
+                self.wots_signatures[i].clone(),
+                AuthPath::new(vec![]),
+                params,
+            );
+
+            match public_key.compute_leaf(public_key.root(), message, &signature, params) {
+                Some(leaf) => leaves.push(leaf),
+                None => return false,
+            }
+        }
+
+        self.proof.verify(
+            &leaves,
+            &indices,
+            public_key.root(),
+            public_key.public_seed(),
+            &params.hash(),
+        )
+    }
+
+    /// first index (4) || count (4) || node count (4) ||
+    /// count × (randomness || WOTS chains) || multi-proof nodes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(&(self.first_index as u32).to_be_bytes());
+        bytes.extend_from_slice(&(self.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&(self.proof.nodes().len() as u32).to_be_bytes());
+
+        for (randomness, wots_signature) in self.randomness.iter().zip(&self.wots_signatures) {
+            bytes.extend_from_slice(randomness);
+            for chain in wots_signature.chains() {
+                bytes.extend_from_slice(chain);
+            }
+        }
+
+        for node in self.proof.nodes() {
+            bytes.extend_from_slice(node);
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8], params: &XMSSParams) -> Result<Self, XMSSSignatureError> {
+        let read_u32 = |offset: usize| {
+            bytes
+                .get(offset..offset + 4)
+                .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        };
+
+        let header = (read_u32(0), read_u32(4), read_u32(8));
+        let (first_index, count, node_count) = match header {
+            (Some(first_index), Some(count), Some(node_count)) => (first_index, count, node_count),
+            _ => {
+                return Err(XMSSSignatureError::InvalidLength {
+                    expected: 12,
+                    actual: bytes.len(),
+                })
+            }
+        };
+
+        let max_leaves = 1usize << params.tree_height();
+        if count == 0 || first_index + count > max_leaves {
+            return Err(XMSSSignatureError::LeafIndexOutOfRange {
+                index: first_index + count.max(1) - 1,
+                max: max_leaves,
+            });
+        }
+
+        // The leaf indices fix how many nodes the multi-proof holds
+        let indices: Vec<usize> = (first_index..first_index + count).collect();
+        let expected_nodes = MultiProof::node_count(&indices, params.tree_height());
+        if node_count != expected_nodes {
+            return Err(XMSSSignatureError::InvalidProofSize {
+                expected: expected_nodes,
+                actual: node_count,
+            });
+        }
+
+        let hash_size = params.hash_size();
+        let signature_size = hash_size + params.len() * hash_size;
+        let expected = 12 + count * signature_size + node_count * hash_size;
+        if bytes.len() != expected {
+            return Err(XMSSSignatureError::InvalidLength {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+
+        let mut chunks = bytes[12..].chunks(hash_size).map(|c| c.to_vec());
+        let mut randomness = Vec::with_capacity(count);
+        let mut wots_signatures = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            randomness.push(chunks.next().expect("length checked"));
+            let chains = chunks.by_ref().take(params.len()).collect();
+            wots_signatures.push(WotsSignature::from_chains(chains));
+        }
+
+        let proof = MultiProof::from_nodes(chunks.collect(), params.tree_height());
+
+        Ok(XmssSignatureBatch {
+            first_index,
+            randomness,
+            wots_signatures,
+            proof,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xmss::XMSSKeypair;
+
+    #[test]
+    fn test_signature_batch_round_trip() {
+        let params = XMSSParams::new(4, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        keypair.sign(b"skip leaf 0");
+
+        let messages: [&[u8]; 4] = [b"one", b"two", b"three", b"four"];
+        let signatures = keypair.sign_chunks(&messages).unwrap();
+
+        let batch = XmssSignatureBatch::new(&signatures).unwrap();
+        let bytes = batch.to_bytes();
+        let individual: usize = signatures.iter().map(|sig| sig.to_bytes().len()).sum();
+        assert!(bytes.len() < individual);
+
+        let parsed = XmssSignatureBatch::from_bytes(&bytes, &params).unwrap();
+        assert_eq!(parsed.first_index(), 1);
+        assert!(parsed.verify(&messages, keypair.public_key(), &params));
+
+        let reordered: [&[u8]; 4] = [b"two", b"one", b"three", b"four"];
+        assert!(!parsed.verify(&reordered, keypair.public_key(), &params));
+
+        assert!(XmssSignatureBatch::from_bytes(&bytes[..bytes.len() - 1], &params).is_err());
+    }
+
+    #[test]
+    fn test_signature_batch_rejects_wrong_node_count() {
+        let params = XMSSParams::new(4, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        keypair.sign(b"skip leaf 0");
+
+        let messages: [&[u8]; 3] = [b"one", b"two", b"three"];
+        let signatures = keypair.sign_chunks(&messages).unwrap();
+        let batch = XmssSignatureBatch::new(&signatures).unwrap();
+        let bytes = batch.to_bytes();
+
+        // Leaves 1..=3 need leaf 0 and one sibling at each of levels 2 and 3
+        let node_count = batch.proof.nodes().len();
+        assert_eq!(node_count, 3);
+        assert_eq!(MultiProof::node_count(&[1, 2, 3], 4), 3);
+
+        // A header claiming one node fewer, with the length adjusted to match
+        let mut forged = bytes[..bytes.len() - params.hash_size()].to_vec();
+        forged[8..12].copy_from_slice(&(node_count as u32 - 1).to_be_bytes());
+        assert_eq!(
+            XmssSignatureBatch::from_bytes(&forged, &params).unwrap_err(),
+            XMSSSignatureError::InvalidProofSize {
+                expected: node_count,
+                actual: node_count - 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_signature_batch_rejects_gaps() {
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let signatures: Vec<_> = (0..3).map(|i| keypair.sign(&[i])).collect();
+
+        assert_eq!(
+            XmssSignatureBatch::new(&[signatures[0].clone(), signatures[2].clone()]).unwrap_err(),
+            XmssError::InvalidBatch
+        );
+        assert_eq!(
+            XmssSignatureBatch::new(&[]).unwrap_err(),
+            XmssError::InvalidBatch
+        );
+    }
+}
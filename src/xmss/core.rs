@@ -1,4 +1,8 @@
-use crate::crypto::hash::HashFunction;
+use crate::crypto::hash::{HashAlg, HashFunction, IncrementalHasher};
+use crate::xmss::error::XmssError;
+use crate::xmss::tree::HashDomain;
+use crate::xmss::wots_plus::public_key_from_signature;
+use subtle::ConstantTimeEq;
 
 #[derive(Debug, Clone)]
 pub struct XMSSParams {
@@ -6,6 +10,7 @@ pub struct XMSSParams {
     winternitz_parameter: usize,
     len: usize,
     use_hypercube: bool,
+    hash: HashAlg,
 }
 
 impl XMSSParams {
@@ -19,6 +24,7 @@ impl XMSSParams {
             winternitz_parameter,
             len,
             use_hypercube: false,
+            hash: HashAlg::default(),
         }
     }
 
@@ -48,6 +54,7 @@ impl XMSSParams {
             winternitz_parameter: w,
             len,
             use_hypercube,
+            hash: HashAlg::default(),
         }
     }
 
@@ -70,6 +77,31 @@ impl XMSSParams {
     pub fn use_hypercube(&self) -> bool {
         self.use_hypercube
     }
+
+    /// Select the tree, chain and message hash (SHA-256 by default)
+    /// Roots, WOTS chains, randomness and authentication path nodes are all
+    /// `hash_size()` bytes; the seeds stay 32 bytes.
+    pub fn with_hash(mut self, hash: HashAlg) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    pub fn hash(&self) -> HashAlg {
+        self.hash
+    }
+
+    /// Output size n of the selected hash in bytes
+    pub fn hash_size(&self) -> usize {
+        self.hash.output_size()
+    }
+
+    /// Number of WOTS chains in each signature and leaf public key
+    /// In hypercube mode this is the TSL dimension v, otherwise `len`.
+    pub fn wots_chain_count(&self) -> usize {
+        crate::xmss::wots_plus::WOTSPlusParams::from_xmss_params(self)
+            .inner_params()
+            .chains()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -79,8 +111,24 @@ pub struct XMSSPublicKey {
 }
 
 impl XMSSPublicKey {
+    /// Public key with a 32-byte (SHA-256) root
     pub fn new(root: Vec<u8>, public_seed: Vec<u8>) -> Self {
         assert_eq!(root.len(), 32, "Root must be 32 bytes");
+        Self::with_root(root, public_seed)
+    }
+
+    /// Public key whose root is sized for the hash selected in `params`
+    pub fn for_params(root: Vec<u8>, public_seed: Vec<u8>, params: &XMSSParams) -> Self {
+        assert_eq!(
+            root.len(),
+            params.hash_size(),
+            "Root must be {} bytes",
+            params.hash_size()
+        );
+        Self::with_root(root, public_seed)
+    }
+
+    fn with_root(root: Vec<u8>, public_seed: Vec<u8>) -> Self {
         assert_eq!(public_seed.len(), 32, "Public seed must be 32 bytes");
 
         XMSSPublicKey { root, public_seed }
@@ -94,47 +142,310 @@ impl XMSSPublicKey {
         &self.public_seed
     }
 
+    /// 32-byte identifier H(root || public_seed) for registry lookups
+    pub fn key_id(&self) -> [u8; 32] {
+        use crate::crypto::hash::SHA256;
+
+        let hash = SHA256::new().hash(&self.to_compact_bytes());
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&hash);
+        id
+    }
+
+    /// Serialize as root || public_seed (`hash_size()` + 32 bytes)
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.root.len() + self.public_seed.len());
+        bytes.extend_from_slice(&self.root);
+        bytes.extend_from_slice(&self.public_seed);
+        bytes
+    }
+
+    /// Parse root || public_seed, with the root sized for `params`
+    pub fn from_compact_bytes(bytes: &[u8], params: &XMSSParams) -> Result<Self, XmssError> {
+        let root_len = params.hash_size();
+        if bytes.len() != root_len + 32 {
+            return Err(XmssError::InvalidKeyLength {
+                expected: root_len + 32,
+                actual: bytes.len(),
+            });
+        }
+
+        Ok(XMSSPublicKey::with_root(
+            bytes[..root_len].to_vec(),
+            bytes[root_len..].to_vec(),
+        ))
+    }
+
     pub fn verify(
         &self,
         message: &[u8],
         signature: &crate::xmss::signature::XMSSSignature,
         params: &XMSSParams,
     ) -> bool {
-        use crate::crypto::hash::{HashFunction, SHA256};
+        match self.compute_root(&self.root, message, signature, params) {
+            // Constant-time comparison so verification time doesn't reveal how many
+            // leading root bytes a forgery got right
+            Some(computed_root) => computed_root.ct_eq(&self.root).into(),
+            None => false,
+        }
+    }
+
+    /// Verify a signature over a message read from `reader`
+    /// The message is hashed as it is read, so it is never held in memory.
+    /// Accepts exactly the signatures `verify` accepts for the same bytes.
+    pub fn verify_reader<R: std::io::Read>(
+        &self,
+        reader: &mut R,
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> std::io::Result<bool> {
+        if Self::check_params(signature, params).is_err() {
+            return Ok(false);
+        }
 
-        let hasher = SHA256::new();
+        let mut digest = Self::message_digest_prefix(&self.root, signature, params);
+        digest.update_reader(reader)?;
+        let message_digest = digest.finalize();
 
-        // Compute message hash
-        let mut msg_data = Vec::new();
-        msg_data.extend_from_slice(signature.randomness());
-        msg_data.extend_from_slice(&self.root);
-        msg_data.extend_from_slice(&(signature.leaf_index() as u32).to_be_bytes());
-        msg_data.extend_from_slice(message);
-        let message_digest = hasher.hash(&msg_data);
+        let computed_root = self.root_from_digest(&message_digest, signature, params);
+        Ok(match computed_root {
+            Some(computed_root) => computed_root.ct_eq(&self.root).into(),
+            None => false,
+        })
+    }
 
-        // Compute leaf from WOTS signature with correct parameters
+    /// Verify a signature, reporting why it was rejected
+    /// A verifier only holds the root, so a mismatch is reported with the
+    /// computed and expected roots and no divergence level.
+    pub fn verify_detailed(
+        &self,
+        message: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Result<(), XmssError> {
+        Self::check_params(signature, params)?;
+
+        let leaf = self
+            .compute_leaf(&self.root, message, signature, params)
+            .ok_or(XmssError::MalformedSignature)?;
+
+        let trace = signature.auth_path().compute_root_trace(
+            &leaf,
+            signature.leaf_index(),
+            &self.public_seed,
+            &params.hash(),
+        );
+        let computed = trace.last().expect("trace contains at least the leaf");
+
+        if computed.ct_eq(&self.root).into() {
+            Ok(())
+        } else {
+            Err(XmssError::RootMismatch {
+                diverged_at_level: None,
+                computed: computed.clone(),
+                expected: self.root.clone(),
+            })
+        }
+    }
+
+    /// Verify against several acceptable roots sharing this key's public seed
+    /// Returns the index of the first root the signature verifies under.
+    /// The message digest binds the root, so a candidate root is computed per
+    /// entry rather than once for the whole set.
+    pub fn verify_against_roots(
+        &self,
+        message: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+        roots: &[&[u8]],
+    ) -> Option<usize> {
+        roots.iter().position(
+            |root| match self.compute_root(root, message, signature, params) {
+                Some(computed_root) => computed_root.ct_eq(root).into(),
+                None => false,
+            },
+        )
+    }
+
+    /// Recompute the tree root from a signature, binding the message digest to `root`
+    fn compute_root(
+        &self,
+        root: &[u8],
+        message: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Option<Vec<u8>> {
+        Self::check_params(signature, params).ok()?;
+        let message_digest = Self::message_digest(root, message, signature, params);
+        self.root_from_digest(&message_digest, signature, params)
+    }
+
+    /// Recompute the tree root from a signature over `message_digest`
+    fn root_from_digest(
+        &self,
+        message_digest: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Option<Vec<u8>> {
         let wots_pk_hash = compute_wots_public_key_hash_with_params(
-            &message_digest,
+            message_digest,
             signature.wots_signature(),
-            &hasher,
+            &params.hash(),
             params,
-        );
+            &self.public_seed,
+            &(signature.leaf_index() as u32).to_be_bytes(),
+        )?;
 
         // Verify authentication path
-        let computed_root = signature.auth_path().compute_root(
+        Some(signature.auth_path().compute_root(
             &wots_pk_hash,
             signature.leaf_index(),
             &self.public_seed,
-            &hasher,
-        );
+            &params.hash(),
+        ))
+    }
+
+    /// Check the signature's shape against the verifier's parameters
+    /// A path for another tree height would otherwise be walked to a
+    /// meaningless root.
+    fn check_params(
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Result<(), XmssError> {
+        let height = signature.auth_path().nodes().len();
+        if height != params.tree_height() {
+            return Err(XmssError::ParamMismatch {
+                param: "tree height",
+                expected: params.tree_height(),
+                actual: height,
+            });
+        }
 
-        let result = computed_root == self.root;
-        if !result {
-            eprintln!("XMSS verify failed:");
-            eprintln!("  Expected root: {:?}", &self.root[0..8]);
-            eprintln!("  Computed root: {:?}", &computed_root[0..8]);
+        let chains = signature.wots_signature().chains().len();
+        if chains != params.wots_chain_count() {
+            return Err(XmssError::ParamMismatch {
+                param: "WOTS chain count",
+                expected: params.wots_chain_count(),
+                actual: chains,
+            });
         }
-        result
+
+        Ok(())
+    }
+
+    /// Reconstruct the leaf (hashed WOTS public key) a signature claims
+    pub(crate) fn compute_leaf(
+        &self,
+        root: &[u8],
+        message: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Option<Vec<u8>> {
+        let message_digest = Self::message_digest(root, message, signature, params);
+
+        // Compute leaf from WOTS signature with correct parameters
+        compute_wots_public_key_hash_with_params(
+            &message_digest,
+            signature.wots_signature(),
+            &params.hash(),
+            params,
+            &self.public_seed,
+            &(signature.leaf_index() as u32).to_be_bytes(),
+        )
+    }
+
+    /// H(r || root || idx_sig || M), the digest the WOTS key signs
+    fn message_digest(
+        root: &[u8],
+        message: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Vec<u8> {
+        let mut digest = Self::message_digest_prefix(root, signature, params);
+        digest.update(message);
+        digest.finalize()
+    }
+
+    /// Hasher that has absorbed r || root || idx_sig, ready for the message
+    fn message_digest_prefix(
+        root: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        params: &XMSSParams,
+    ) -> Box<dyn IncrementalHasher> {
+        message_digest_prefix(params, signature.randomness(), root, signature.leaf_index())
+    }
+
+    /// Verify against a cached leaf, the WOTS+ public key hash of the signing leaf
+    /// Skips the authentication path walk, so `wots_pk_hash` must already be
+    /// known to belong to this key's tree, e.g. from `WOTSPlusKeypair::public_key_hash`
+    /// or an earlier `verify`; the path in the signature is not checked. The
+    /// WOTS+ chains are still reconstructed: the public key hash alone does
+    /// not bind the message, only a signature that hashes forward to it does.
+    pub fn verify_with_wots_pk(
+        &self,
+        message: &[u8],
+        signature: &crate::xmss::signature::XMSSSignature,
+        wots_pk_hash: &[u8],
+        params: &XMSSParams,
+    ) -> bool {
+        if Self::check_params(signature, params).is_err() {
+            return false;
+        }
+
+        match self.compute_leaf(&self.root, message, signature, params) {
+            Some(leaf) => leaf.ct_eq(wots_pk_hash).into(),
+            None => false,
+        }
+    }
+
+    /// Verify independent (message, signature) pairs under this key
+    /// Entry i of the result reports whether item i verified. With the `rayon`
+    /// feature the items are verified in parallel.
+    pub fn verify_batch(
+        &self,
+        items: &[(&[u8], &crate::xmss::signature::XMSSSignature)],
+        params: &XMSSParams,
+    ) -> Vec<bool> {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            items
+                .par_iter()
+                .map(|&(message, signature)| self.verify(message, signature, params))
+                .collect()
+        }
+
+        #[cfg(not(feature = "rayon"))]
+        {
+            items
+                .iter()
+                .map(|&(message, signature)| self.verify(message, signature, params))
+                .collect()
+        }
+    }
+
+    /// Verify signatures produced by `XMSSKeypair::sign_chunks`
+    /// Each chunk must verify under its signature, and the signatures must use
+    /// strictly consecutive leaf indices in chunk order.
+    pub fn verify_chunks(
+        &self,
+        chunks: &[&[u8]],
+        signatures: &[crate::xmss::signature::XMSSSignature],
+        params: &XMSSParams,
+    ) -> bool {
+        if chunks.len() != signatures.len() {
+            return false;
+        }
+
+        let ordered = signatures
+            .windows(2)
+            .all(|pair| pair[1].leaf_index() == pair[0].leaf_index() + 1);
+
+        ordered
+            && chunks
+                .iter()
+                .zip(signatures)
+                .all(|(chunk, signature)| self.verify(chunk, signature, params))
     }
 }
 
@@ -156,11 +467,17 @@ impl XMSSPrivateKey {
         sk_prf: Vec<u8>,
         public_seed: Vec<u8>,
         root: Vec<u8>,
+        params: &XMSSParams,
     ) -> Self {
         assert_eq!(sk_seed.len(), 32, "SK seed must be 32 bytes");
         assert_eq!(sk_prf.len(), 32, "SK PRF must be 32 bytes");
         assert_eq!(public_seed.len(), 32, "Public seed must be 32 bytes");
-        assert_eq!(root.len(), 32, "Root must be 32 bytes");
+        assert_eq!(
+            root.len(),
+            params.hash_size(),
+            "Root must be {} bytes",
+            params.hash_size()
+        );
 
         XMSSPrivateKey {
             leaf_index,
@@ -180,6 +497,32 @@ impl XMSSPrivateKey {
         self.leaf_index += 1;
     }
 
+    /// Whether leaf `index` has not been consumed by a signature yet
+    /// Only guards this in-memory key against signing twice at one leaf. It
+    /// is not forward security: anyone holding `sk_seed` can rebuild the key
+    /// with `XMSSKeypair::generate_from_seed` or `restore` and sign at any leaf.
+    pub fn can_sign_index(&self, index: usize) -> bool {
+        index >= self.leaf_index
+    }
+
+    /// Derive the WOTS keypair of leaf `index` for signing
+    /// Refused for leaves below `leaf_index`, so the key never hands out a
+    /// one-time key twice. This is an index gate, not forward security:
+    /// `sk_seed` is still held and a stolen seed re-derives every leaf.
+    pub fn wots_keypair(
+        &self,
+        index: usize,
+        params: &XMSSParams,
+    ) -> Result<crate::xmss::wots_plus::WOTSPlusKeypair, XmssError> {
+        if !self.can_sign_index(index) {
+            return Err(XmssError::LeafAlreadyUsed { index });
+        }
+
+        let wots_params = crate::xmss::wots_plus::WOTSPlusParams::from_xmss_params(params);
+        let address = (index as u32).to_be_bytes();
+        Ok(wots_params.generate_keypair(&self.sk_seed, &self.public_seed, &address))
+    }
+
     pub fn sk_seed(&self) -> &[u8] {
         &self.sk_seed
     }
@@ -216,18 +559,104 @@ pub struct XMSSPrivateKeyState {
     pub root: Vec<u8>,
 }
 
-fn compute_wots_public_key_hash_with_params(
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for XMSSPrivateKey {
+    fn zeroize(&mut self) {
+        // Secrets are wiped in place, keeping their length and allocation
+        for key in self._wots_keys.iter_mut() {
+            key.as_mut_slice().zeroize();
+        }
+        self.sk_seed.as_mut_slice().zeroize();
+        self.sk_prf.as_mut_slice().zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for XMSSPrivateKey {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for XMSSPrivateKey {}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for XMSSPrivateKeyState {
+    fn zeroize(&mut self) {
+        self.sk_seed.as_mut_slice().zeroize();
+        self.sk_prf.as_mut_slice().zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for XMSSPrivateKeyState {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for XMSSPrivateKeyState {}
+
+/// Start the message digest Hash(r || root || idx_sig || M) with everything but M.
+/// Signing and verification share it so both hash the same prefix.
+pub(crate) fn message_digest_prefix(
+    params: &XMSSParams,
+    randomness: &[u8],
+    root: &[u8],
+    leaf_index: usize,
+) -> Box<dyn IncrementalHasher> {
+    let mut digest = HashDomain::MessageDigest.hasher(&params.hash());
+    digest.update(randomness);
+    digest.update(root);
+    digest.update(&(leaf_index as u32).to_be_bytes());
+    digest
+}
+
+/// Reconstruct the WOTS public key from a signature and hash it into a leaf.
+/// Returns `None` if the encoded message does not provide exactly one digit
+/// per signature chain, since no leaf can be reconstructed in that case.
+/// `public_seed` and `address` must be those the WOTS+ key was derived with.
+pub(crate) fn compute_wots_public_key_hash_with_params(
     message_digest: &[u8],
     wots_signature: &crate::wots::WotsSignature,
     hasher: &dyn HashFunction,
     params: &XMSSParams,
-) -> Vec<u8> {
-    use crate::wots::hash_chain;
+    public_seed: &[u8],
+    address: &[u8],
+) -> Option<Vec<u8>> {
+    let chains = wots_signature.chains().len();
+    let message_values = message_digits(message_digest, params, chains);
+
+    // Reconstruct WOTS+ public key chains
+    let pk_chains = public_key_from_signature(
+        hasher,
+        public_seed,
+        address,
+        &message_values,
+        wots_signature,
+        params.winternitz_parameter(),
+    )?;
 
+    Some(hash_wots_public_key(&pk_chains, hasher))
+}
+
+/// Leaf value of a WOTS public key: the Leaf-domain hash of its concatenated chains
+pub(crate) fn hash_wots_public_key(pk_chains: &[Vec<u8>], hasher: &dyn HashFunction) -> Vec<u8> {
+    let mut state = HashDomain::Leaf.hasher(hasher);
+    for chain in pk_chains {
+        state.update(chain);
+    }
+
+    state.finalize()
+}
+
+/// WOTS digits in [0, w-1] signed for a message digest, as in signing
+fn message_digits(message_digest: &[u8], params: &XMSSParams, chains: usize) -> Vec<usize> {
     let w = params.winternitz_parameter();
-    let chains = wots_signature.chains().len();
 
-    let message_values = if params.use_hypercube() {
+    if params.use_hypercube() {
         // For hypercube encoding, apply the same TSL encoding as in signing
         use crate::core::encoding::EncodingScheme;
         use crate::schemes::tsl::{TSLConfig, TSL};
@@ -254,24 +683,7 @@ fn compute_wots_public_key_hash_with_params(
     } else {
         // Standard base-w encoding
         base_w_from_bytes(message_digest, w, chains)
-    };
-
-    // Reconstruct WOTS public key chains
-    let mut pk_chains = Vec::new();
-    for (i, sig_chain) in wots_signature.chains().iter().enumerate() {
-        let x_i = message_values[i];
-        let remaining_iterations = w - 1 - x_i;
-        let pk_chain = hash_chain(hasher, sig_chain, remaining_iterations);
-        pk_chains.push(pk_chain);
     }
-
-    // Hash all chains together to get public key hash
-    let mut data = Vec::new();
-    for chain in &pk_chains {
-        data.extend_from_slice(chain);
-    }
-
-    hasher.hash(&data)
 }
 
 fn base_w_from_bytes(bytes: &[u8], w: usize, out_len: usize) -> Vec<usize> {
@@ -345,6 +757,22 @@ mod tests {
         assert!(params.len() < 67); // Optimized chain count should be less than standard w=67
     }
 
+    #[test]
+    fn test_wots_chain_count() {
+        use crate::core::encoding::EncodingScheme;
+        use crate::schemes::tsl::{TSLConfig, TSL};
+
+        let standard = XMSSParams::new_with_hypercube(4, 128, false);
+        let hypercube = XMSSParams::new_with_hypercube(4, 128, true);
+
+        assert_eq!(standard.wots_chain_count(), 67);
+        assert!(hypercube.wots_chain_count() < standard.wots_chain_count());
+        assert_eq!(
+            hypercube.wots_chain_count(),
+            TSL::new(TSLConfig::new(128)).dimension()
+        );
+    }
+
     #[test]
     fn test_xmss_deterministic_params() {
         let params1 = XMSSParams::new(4, 67, 16);
@@ -357,4 +785,281 @@ mod tests {
         );
         assert_eq!(params1.len(), params2.len());
     }
+
+    #[test]
+    fn test_verify_rejects_chain_count_mismatch() {
+        use crate::wots::WotsSignature;
+        use crate::xmss::{XMSSKeypair, XMSSSignature};
+
+        let params = XMSSParams::new_with_hypercube(2, 128, true);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"chain count mismatch";
+        let signature = keypair.sign(message);
+
+        // Append an extra chain so the TSL encoder yields fewer digits than chains
+        let mut chains = signature.wots_signature().chains().to_vec();
+        chains.push(vec![0u8; 32]);
+        let tampered = XMSSSignature::new(
+            signature.leaf_index(),
+            signature.randomness().to_vec(),
+            WotsSignature::from_chains(chains),
+            signature.auth_path().clone(),
+            &params,
+        );
+
+        assert!(keypair.public_key().verify(message, &signature, &params));
+        assert!(!keypair.public_key().verify(message, &tampered, &params));
+    }
+
+    #[test]
+    fn test_verify_rejects_root_differing_in_last_byte() {
+        use crate::xmss::XMSSKeypair;
+
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"constant time root check";
+        let signature = keypair.sign(message);
+
+        assert!(keypair.public_key().verify(message, &signature, &params));
+
+        let mut root = keypair.public_key().root().to_vec();
+        root[31] ^= 0x01;
+        let tampered_pk = XMSSPublicKey::new(root, keypair.public_key().public_seed().to_vec());
+        assert!(!tampered_pk.verify(message, &signature, &params));
+    }
+
+    #[test]
+    fn test_public_key_id_and_compact_bytes() {
+        let pk1 = XMSSPublicKey::new(vec![1u8; 32], vec![2u8; 32]);
+        let pk2 = XMSSPublicKey::new(vec![1u8; 32], vec![3u8; 32]);
+
+        assert_eq!(pk1.key_id(), pk1.clone().key_id());
+        assert_ne!(pk1.key_id(), pk2.key_id());
+
+        let params = XMSSParams::new(2, 16, 16);
+        let bytes = pk1.to_compact_bytes();
+        assert_eq!(bytes.len(), 64);
+        let restored = XMSSPublicKey::from_compact_bytes(&bytes, &params).unwrap();
+        assert_eq!(restored.root(), pk1.root());
+        assert_eq!(restored.public_seed(), pk1.public_seed());
+        assert_eq!(restored.key_id(), pk1.key_id());
+
+        assert_eq!(
+            XMSSPublicKey::from_compact_bytes(&bytes[..63], &params).unwrap_err(),
+            XmssError::InvalidKeyLength {
+                expected: 64,
+                actual: 63
+            }
+        );
+
+        // A 64-byte hash gives a 96-byte encoding
+        let params = params.with_hash(HashAlg::Sha512);
+        let pk = XMSSPublicKey::for_params(vec![4u8; 64], vec![5u8; 32], &params);
+        let bytes = pk.to_compact_bytes();
+        assert_eq!(bytes.len(), 96);
+        let restored = XMSSPublicKey::from_compact_bytes(&bytes, &params).unwrap();
+        assert_eq!(restored.root(), pk.root());
+        assert_eq!(restored.public_seed(), pk.public_seed());
+    }
+
+    #[test]
+    fn test_verify_against_roots() {
+        use crate::xmss::XMSSKeypair;
+
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"rotating keys";
+        let signature = keypair.sign(message);
+
+        let root = keypair.public_key().root().to_vec();
+        let other_a = [0xAAu8; 32];
+        let other_b = [0xBBu8; 32];
+
+        let accepted: [&[u8]; 3] = [&other_a, &root, &other_b];
+        assert_eq!(
+            keypair
+                .public_key()
+                .verify_against_roots(message, &signature, &params, &accepted),
+            Some(1)
+        );
+
+        let rejected: [&[u8]; 2] = [&other_a, &other_b];
+        assert_eq!(
+            keypair
+                .public_key()
+                .verify_against_roots(message, &signature, &params, &rejected),
+            None
+        );
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_private_key_zeroize() {
+        use zeroize::Zeroize;
+
+        let mut key = XMSSPrivateKey::new(
+            0,
+            vec![],
+            vec![7u8; 32],
+            vec![9u8; 32],
+            vec![1u8; 32],
+            vec![2u8; 32],
+            &XMSSParams::new(2, 16, 16),
+        );
+
+        // Drop runs exactly this; the wiped buffers are still owned by the key
+        key.zeroize();
+        assert_eq!(key.sk_seed(), &[0u8; 32][..]);
+        assert_eq!(key.sk_prf(), &[0u8; 32][..]);
+
+        let mut state = XMSSPrivateKeyState {
+            leaf_index: 0,
+            sk_seed: vec![7u8; 32],
+            sk_prf: vec![9u8; 32],
+            public_seed: vec![1u8; 32],
+            root: vec![2u8; 32],
+        };
+        state.zeroize();
+        assert_eq!(state.sk_seed, vec![0u8; 32]);
+        assert_eq!(state.sk_prf, vec![0u8; 32]);
+    }
+
+    #[test]
+    fn test_verify_detailed_reports_roots() {
+        use crate::xmss::{XMSSKeypair, XMSSSignature};
+
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"detailed verification";
+        let signature = keypair.sign(message);
+        let public_key = keypair.public_key();
+
+        assert_eq!(
+            public_key.verify_detailed(message, &signature, &params),
+            Ok(())
+        );
+
+        // Corrupt an authentication path node
+        let mut bytes = signature.to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let tampered = XMSSSignature::from_bytes(&bytes, &params).unwrap();
+
+        match public_key.verify_detailed(message, &tampered, &params) {
+            Err(XmssError::RootMismatch {
+                diverged_at_level,
+                computed,
+                expected,
+            }) => {
+                assert_eq!(diverged_at_level, None);
+                assert_eq!(expected, public_key.root());
+                assert_eq!(computed.len(), 32);
+                assert_ne!(computed, expected);
+            }
+            other => panic!("expected RootMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        use crate::xmss::XMSSKeypair;
+
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let signatures: Vec<_> = (0..4)
+            .map(|i| keypair.sign(format!("Message {}", i).as_bytes()))
+            .collect();
+
+        let mut corrupted = signatures[2].to_bytes();
+        corrupted[10] ^= 0x01;
+        let corrupted = crate::xmss::XMSSSignature::from_bytes(&corrupted, &params).unwrap();
+
+        let items: Vec<(&[u8], &crate::xmss::XMSSSignature)> = vec![
+            (b"Message 0", &signatures[0]),
+            (b"wrong message", &signatures[1]),
+            (b"Message 2", &corrupted),
+            (b"Message 3", &signatures[3]),
+            (b"Message 0", &signatures[3]),
+        ];
+
+        let results = keypair.public_key().verify_batch(&items, &params);
+        assert_eq!(results, vec![true, false, false, true, false]);
+        assert!(keypair.public_key().verify_batch(&[], &params).is_empty());
+    }
+
+    #[test]
+    fn test_verify_with_wots_pk() {
+        use crate::xmss::{WOTSPlusParams, XMSSKeypair};
+
+        let params = XMSSParams::new(2, 16, 16);
+        let seed = [7u8; 96];
+        let mut keypair = XMSSKeypair::generate_from_seed(&params, &seed);
+        keypair.sign(b"skip leaf 0");
+        let message = b"known WOTS public key";
+        let signature = keypair.sign(message);
+        let public_key = keypair.public_key();
+
+        let wots_params = WOTSPlusParams::from_xmss_params(&params);
+        let wots_pk_hash = |idx: u32| {
+            wots_params
+                .generate_keypair(&seed[0..32], &seed[64..96], &idx.to_be_bytes())
+                .public_key_hash()
+        };
+        let correct = wots_pk_hash(1);
+        let wrong = wots_pk_hash(2);
+
+        assert_eq!(
+            public_key.verify_with_wots_pk(message, &signature, &correct, &params),
+            public_key.verify(message, &signature, &params)
+        );
+        assert!(public_key.verify_with_wots_pk(message, &signature, &correct, &params));
+        assert!(!public_key.verify_with_wots_pk(message, &signature, &wrong, &params));
+        assert!(!public_key.verify_with_wots_pk(b"other", &signature, &correct, &params));
+    }
+
+    #[test]
+    fn test_verify_detailed_reports_height_mismatch() {
+        use crate::xmss::XMSSKeypair;
+
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"height mismatch";
+        let signature = keypair.sign(message);
+
+        let taller = XMSSParams::new(3, 16, 16);
+        assert!(!keypair.public_key().verify(message, &signature, &taller));
+        assert_eq!(
+            keypair
+                .public_key()
+                .verify_detailed(message, &signature, &taller),
+            Err(XmssError::ParamMismatch {
+                param: "tree height",
+                expected: 3,
+                actual: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_verify_detailed_reports_chain_count_mismatch() {
+        use crate::xmss::XMSSKeypair;
+
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"chain count mismatch";
+        let signature = keypair.sign(message);
+
+        let longer = XMSSParams::new(2, 16, 17);
+        assert!(!keypair.public_key().verify(message, &signature, &longer));
+        assert_eq!(
+            keypair
+                .public_key()
+                .verify_detailed(message, &signature, &longer),
+            Err(XmssError::ParamMismatch {
+                param: "WOTS chain count",
+                expected: 17,
+                actual: 16,
+            })
+        );
+    }
 }
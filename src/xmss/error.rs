@@ -0,0 +1,56 @@
+use thiserror::Error;
+
+/// Errors returned by fallible XMSS operations
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum XmssError {
+    #[error(
+        "XMSS key capacity insufficient: {requested} signatures requested, {remaining} remaining"
+    )]
+    InsufficientCapacity { requested: usize, remaining: usize },
+
+    #[error("XMSS root does not match the tree derived from the key seeds")]
+    RootInconsistent,
+
+    #[error("XMSS leaf {index} was already used for a signature")]
+    LeafAlreadyUsed { index: usize },
+
+    #[error("XMSS signature does not match the public key parameters")]
+    MalformedSignature,
+
+    /// The signature was made under different parameters than the verifier's
+    #[error("XMSS signature {param} is {actual}, parameters expect {expected}")]
+    ParamMismatch {
+        param: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+
+    #[error("XMSS public key encoding is {actual} bytes, expected {expected}")]
+    InvalidKeyLength { expected: usize, actual: usize },
+
+    #[error("XMSS signature batch must be non-empty with consecutive leaf indices")]
+    InvalidBatch,
+
+    /// The authentication path leads to a different root
+    /// `diverged_at_level` is the lowest tree level whose recomputed node is
+    /// known to be wrong; `None` when only the root could be compared.
+    #[error("XMSS root mismatch: computed {computed:02x?}, expected {expected:02x?}")]
+    RootMismatch {
+        diverged_at_level: Option<usize>,
+        computed: Vec<u8>,
+        expected: Vec<u8>,
+    },
+}
+
+/// Errors returned when parsing an `XMSSSignature` from bytes
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum XMSSSignatureError {
+    #[error("Invalid signature length: expected {expected}, got {actual}")]
+    InvalidLength { expected: usize, actual: usize },
+
+    #[error("Signature leaf index {index} out of range for {max} leaves")]
+    LeafIndexOutOfRange { index: usize, max: usize },
+
+    #[error("Multi-proof has {actual} nodes, but its leaf indices need {expected}")]
+    InvalidProofSize { expected: usize, actual: usize },
+}
@@ -1,14 +1,22 @@
-use crate::crypto::hash::{HashFunction, SHA256};
+use crate::crypto::hash::IncrementalHasher;
 use crate::crypto::random::{OsSecureRandom, SecureRandom};
-use crate::xmss::core::{XMSSParams, XMSSPrivateKey, XMSSPrivateKeyState, XMSSPublicKey};
+use crate::signer::{Signer, Verifier};
+use crate::xmss::core::{
+    message_digest_prefix, XMSSParams, XMSSPrivateKey, XMSSPrivateKeyState, XMSSPublicKey,
+};
+use crate::xmss::error::XmssError;
 use crate::xmss::signature::XMSSSignature;
-use crate::xmss::tree::MerkleTree;
+use crate::xmss::tree::{HashDomain, MerkleTree};
 use crate::xmss::wots_plus::WOTSPlusParams;
+use std::io::{self, Read, Seek, SeekFrom};
+use subtle::ConstantTimeEq;
 
 pub struct XMSSKeypair {
     public_key: XMSSPublicKey,
     private_key: XMSSPrivateKey,
     params: XMSSParams,
+    /// Merkle tree over the leaves, built on first use after `restore`
+    tree: Option<MerkleTree>,
 }
 
 impl XMSSKeypair {
@@ -20,33 +28,38 @@ impl XMSSKeypair {
     }
 
     pub fn generate_from_seed(params: &XMSSParams, seed: &[u8]) -> Self {
+        Self::generate_with(params, seed, build_tree)
+    }
+
+    /// Same keypair as `generate_from_seed`, hashing the leaves on all cores
+    #[cfg(feature = "rayon")]
+    pub fn generate_from_seed_parallel(params: &XMSSParams, seed: &[u8]) -> Self {
+        Self::generate_with(params, seed, build_tree_parallel)
+    }
+
+    fn generate_with(
+        params: &XMSSParams,
+        seed: &[u8],
+        build_tree: fn(&XMSSParams, &[u8], &[u8]) -> MerkleTree,
+    ) -> Self {
         assert_eq!(seed.len(), 96, "Seed must be 96 bytes");
 
         let sk_seed = seed[0..32].to_vec();
         let sk_prf = seed[32..64].to_vec();
         let public_seed = seed[64..96].to_vec();
 
-        let hasher = SHA256::new();
-        let num_leaves = 1 << params.tree_height();
-        let mut leaves = Vec::with_capacity(num_leaves);
-        let wots_params = WOTSPlusParams::from_xmss_params(params);
-
-        for i in 0..num_leaves {
-            let address = (i as u32).to_be_bytes();
-            let keypair = wots_params.generate_keypair(&sk_seed, &address);
-            leaves.push(keypair.public_key_hash());
-        }
-
-        let tree = MerkleTree::build(&leaves, &public_seed, &hasher);
+        let tree = build_tree(params, &sk_seed, &public_seed);
         let root = tree.root().to_vec();
 
-        let public_key = XMSSPublicKey::new(root.clone(), public_seed.clone());
-        let private_key = XMSSPrivateKey::new(0, vec![], sk_seed, sk_prf, public_seed, root);
+        let public_key = XMSSPublicKey::for_params(root.clone(), public_seed.clone(), params);
+        let private_key =
+            XMSSPrivateKey::new(0, vec![], sk_seed, sk_prf, public_seed, root, params);
 
         XMSSKeypair {
             public_key,
             private_key,
             params: params.clone(),
+            tree: Some(tree),
         }
     }
 
@@ -70,64 +83,247 @@ impl XMSSKeypair {
             panic!("XMSS key exhausted");
         }
 
-        let hasher = SHA256::new();
+        let signature = self.sign_at(leaf_idx, message);
+        self.private_key.increment_leaf_index();
 
-        // Compute PRF(SK_PRF, idx_sig || M)
-        let mut r_data = Vec::new();
-        r_data.extend_from_slice(self.private_key.sk_prf());
-        r_data.extend_from_slice(&(leaf_idx as u32).to_be_bytes());
-        r_data.extend_from_slice(message);
-        let randomness = hasher.hash(&r_data);
+        signature
+    }
 
-        // Hash(r || root || idx_sig || M)
-        let mut msg_data = Vec::new();
-        msg_data.extend_from_slice(&randomness);
-        msg_data.extend_from_slice(self.private_key.root());
-        msg_data.extend_from_slice(&(leaf_idx as u32).to_be_bytes());
-        msg_data.extend_from_slice(message);
-        let message_digest = hasher.hash(&msg_data);
-
-        let wots_params = WOTSPlusParams::from_xmss_params(&self.params);
-        let address = (leaf_idx as u32).to_be_bytes();
-        let wots_keypair = wots_params.generate_keypair(self.private_key.sk_seed(), &address);
-        let wots_signature = wots_keypair.sign(&message_digest);
-
-        let num_leaves = 1 << self.params.tree_height();
-        let mut leaves = Vec::with_capacity(num_leaves);
-
-        for i in 0..num_leaves {
-            let addr = (i as u32).to_be_bytes();
-            let kp = wots_params.generate_keypair(self.private_key.sk_seed(), &addr);
-            leaves.push(kp.public_key_hash());
+    /// Sign a message read from `reader`, hashing it as it is read
+    /// The message is read twice from the current position, once for the
+    /// randomness PRF and once for the digest, so it is never held in memory.
+    /// The signature is byte-identical to `sign` over the same bytes. The leaf
+    /// index only advances once the signature is complete.
+    pub fn sign_reader<R: Read + Seek>(&mut self, reader: &mut R) -> io::Result<XMSSSignature> {
+        let leaf_idx = self.private_key.leaf_index();
+        let max_signatures = 1 << self.params.tree_height();
+
+        if leaf_idx >= max_signatures {
+            panic!("XMSS key exhausted");
         }
 
-        let tree = MerkleTree::build(&leaves, self.private_key.public_seed(), &hasher);
-        let auth_path = tree.authentication_path(leaf_idx);
+        let start = reader.stream_position()?;
+
+        let mut prf = self.prf_prefix(leaf_idx);
+        prf.update_reader(reader)?;
+        let randomness = prf.finalize();
 
+        reader.seek(SeekFrom::Start(start))?;
+        let mut digest = self.digest_prefix(leaf_idx, &randomness);
+        digest.update_reader(reader)?;
+        let message_digest = digest.finalize();
+
+        let signature = self.sign_digest(leaf_idx, randomness, &message_digest);
         self.private_key.increment_leaf_index();
 
-        XMSSSignature::new(leaf_idx, randomness, wots_signature, auth_path)
+        Ok(signature)
     }
 
-    pub fn restore(params: &XMSSParams, state: XMSSPrivateKeyState) -> Self {
-        let public_key = XMSSPublicKey::new(state.root.clone(), state.public_seed.clone());
+    /// Re-derive the signature made at `index` by the keypair generated from `seed`
+    /// Signing is deterministic (the randomness is PRF(SK_PRF, idx || M)), so the
+    /// result matches the original byte for byte. No key state is advanced.
+    pub fn rederive_signature(
+        seed: &[u8],
+        params: &XMSSParams,
+        index: usize,
+        message: &[u8],
+    ) -> XMSSSignature {
+        assert!(index < 1 << params.tree_height(), "Leaf index out of range");
+
+        let mut keypair = Self::generate_from_seed(params, seed);
+        keypair.sign_at(index, message)
+    }
+
+    /// Sign at a given leaf without touching the leaf index
+    fn sign_at(&mut self, leaf_idx: usize, message: &[u8]) -> XMSSSignature {
+        // Compute PRF(SK_PRF, idx_sig || M)
+        let mut prf = self.prf_prefix(leaf_idx);
+        prf.update(message);
+        let randomness = prf.finalize();
+
+        // Hash(r || root || idx_sig || M)
+        let mut digest = self.digest_prefix(leaf_idx, &randomness);
+        digest.update(message);
+        let message_digest = digest.finalize();
+
+        self.sign_digest(leaf_idx, randomness, &message_digest)
+    }
+
+    /// PRF state that has absorbed SK_PRF || idx_sig, ready for the message
+    fn prf_prefix(&self, leaf_idx: usize) -> Box<dyn IncrementalHasher> {
+        let mut prf = HashDomain::MessagePrf.hasher(&self.params.hash());
+        prf.update(self.private_key.sk_prf());
+        prf.update(&(leaf_idx as u32).to_be_bytes());
+        prf
+    }
+
+    /// Digest state that has absorbed r || root || idx_sig, ready for the message
+    fn digest_prefix(&self, leaf_idx: usize, randomness: &[u8]) -> Box<dyn IncrementalHasher> {
+        message_digest_prefix(&self.params, randomness, self.private_key.root(), leaf_idx)
+    }
+
+    /// WOTS-sign `message_digest` at a leaf and attach its authentication path
+    fn sign_digest(
+        &mut self,
+        leaf_idx: usize,
+        randomness: Vec<u8>,
+        message_digest: &[u8],
+    ) -> XMSSSignature {
+        let wots_keypair = self
+            .private_key
+            .wots_keypair(leaf_idx, &self.params)
+            .expect("XMSS leaf already used");
+        let wots_signature = wots_keypair.sign(message_digest);
+
+        let auth_path = self.tree().authentication_path(leaf_idx);
+
+        XMSSSignature::new(
+            leaf_idx,
+            randomness,
+            wots_signature,
+            auth_path,
+            &self.params,
+        )
+    }
+
+    /// Sign each chunk at successive leaves
+    /// Fails without consuming any leaf if the remaining capacity is smaller
+    /// than the number of chunks.
+    pub fn sign_chunks(&mut self, chunks: &[&[u8]]) -> Result<Vec<XMSSSignature>, XmssError> {
+        let max_signatures = 1 << self.params.tree_height();
+        let remaining = max_signatures - self.private_key.leaf_index().min(max_signatures);
+
+        if chunks.len() > remaining {
+            return Err(XmssError::InsufficientCapacity {
+                requested: chunks.len(),
+                remaining,
+            });
+        }
+
+        Ok(chunks.iter().map(|chunk| self.sign(chunk)).collect())
+    }
+
+    pub fn restore(params: &XMSSParams, mut state: XMSSPrivateKeyState) -> Self {
+        let public_key =
+            XMSSPublicKey::for_params(state.root.clone(), state.public_seed.clone(), params);
+
+        // Move the secrets out rather than copying them, so no unzeroized
+        // duplicate is left behind when `state` is dropped
         let private_key = XMSSPrivateKey::new(
             state.leaf_index,
             vec![],
-            state.sk_seed,
-            state.sk_prf,
-            state.public_seed,
-            state.root,
+            std::mem::take(&mut state.sk_seed),
+            std::mem::take(&mut state.sk_prf),
+            std::mem::take(&mut state.public_seed),
+            std::mem::take(&mut state.root),
+            params,
         );
 
         XMSSKeypair {
             public_key,
             private_key,
             params: params.clone(),
+            tree: None,
+        }
+    }
+
+    /// Cached Merkle tree, rebuilt from the seeds if absent
+    fn tree(&mut self) -> &MerkleTree {
+        self.tree.get_or_insert_with(|| {
+            build_tree(
+                &self.params,
+                self.private_key.sk_seed(),
+                self.private_key.public_seed(),
+            )
+        })
+    }
+
+    /// Check that the stored roots match the tree derived from the seeds
+    /// Intended for use after `restore`, before signing with possibly corrupted state.
+    pub fn validate(&self) -> Result<(), XmssError> {
+        let tree = build_tree(
+            &self.params,
+            self.private_key.sk_seed(),
+            self.private_key.public_seed(),
+        );
+
+        let private_ok: bool = tree.root().ct_eq(self.private_key.root()).into();
+        let public_ok: bool = tree.root().ct_eq(self.public_key.root()).into();
+        let seed_ok = self.public_key.public_seed() == self.private_key.public_seed();
+
+        if private_ok && public_ok && seed_ok {
+            Ok(())
+        } else {
+            Err(XmssError::RootInconsistent)
         }
     }
 }
 
+impl Signer for XMSSKeypair {
+    type Message = [u8];
+    type Signature = XMSSSignature;
+    type Error = XmssError;
+
+    /// Fails with `InsufficientCapacity` instead of panicking once the key is exhausted
+    fn sign(&mut self, message: &[u8]) -> Result<XMSSSignature, XmssError> {
+        self.sign_chunks(&[message])
+            .map(|mut signatures| signatures.remove(0))
+    }
+}
+
+impl Verifier for XMSSKeypair {
+    type Message = [u8];
+    type Signature = XMSSSignature;
+
+    fn verify(&self, message: &[u8], signature: &XMSSSignature) -> bool {
+        self.public_key.verify(message, signature, &self.params)
+    }
+}
+
+/// Build the Merkle tree over all WOTS+ leaves derived from `sk_seed`
+fn build_tree(params: &XMSSParams, sk_seed: &[u8], public_seed: &[u8]) -> MerkleTree {
+    let hasher = params.hash();
+    let num_leaves = 1 << params.tree_height();
+    let mut leaves = Vec::with_capacity(num_leaves);
+    let wots_params = WOTSPlusParams::from_xmss_params(params);
+
+    for i in 0..num_leaves {
+        leaves.push(leaf_hash(&wots_params, sk_seed, public_seed, i));
+    }
+
+    MerkleTree::build(&leaves, public_seed, &hasher)
+}
+
+/// `build_tree` with the leaves hashed in parallel; each leaf only depends
+/// on `sk_seed` and its index, so the tree is identical
+#[cfg(feature = "rayon")]
+fn build_tree_parallel(params: &XMSSParams, sk_seed: &[u8], public_seed: &[u8]) -> MerkleTree {
+    use rayon::prelude::*;
+
+    let num_leaves = 1usize << params.tree_height();
+    let wots_params = WOTSPlusParams::from_xmss_params(params);
+
+    let leaves: Vec<Vec<u8>> = (0..num_leaves)
+        .into_par_iter()
+        .map(|i| leaf_hash(&wots_params, sk_seed, public_seed, i))
+        .collect();
+
+    MerkleTree::build(&leaves, public_seed, &params.hash())
+}
+
+/// Hashed WOTS+ public key at leaf `i`
+fn leaf_hash(
+    wots_params: &WOTSPlusParams,
+    sk_seed: &[u8],
+    public_seed: &[u8],
+    i: usize,
+) -> Vec<u8> {
+    let address = (i as u32).to_be_bytes();
+    wots_params
+        .generate_keypair(sk_seed, public_seed, &address)
+        .public_key_hash()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,4 +417,227 @@ mod tests {
             restored_keypair.params()
         ));
     }
+
+    #[test]
+    fn test_xmss_sign_and_verify_chunks() {
+        let params = XMSSParams::new(3, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        keypair.sign(b"Message 0");
+
+        let chunks: [&[u8]; 3] = [b"chunk 1", b"chunk 2", b"chunk 3"];
+        let signatures = keypair.sign_chunks(&chunks).unwrap();
+
+        assert_eq!(signatures.len(), 3);
+        assert_eq!(signatures[0].leaf_index(), 1);
+        assert_eq!(keypair.private_key().leaf_index(), 4);
+        assert!(keypair
+            .public_key()
+            .verify_chunks(&chunks, &signatures, keypair.params()));
+
+        // Reordered signatures fail the index ordering check
+        let reordered = vec![
+            signatures[1].clone(),
+            signatures[0].clone(),
+            signatures[2].clone(),
+        ];
+        let reordered_chunks: [&[u8]; 3] = [b"chunk 2", b"chunk 1", b"chunk 3"];
+        assert!(!keypair.public_key().verify_chunks(
+            &reordered_chunks,
+            &reordered,
+            keypair.params()
+        ));
+
+        // Missing signatures fail
+        assert!(!keypair
+            .public_key()
+            .verify_chunks(&chunks, &signatures[..2], keypair.params()));
+    }
+
+    #[test]
+    fn test_xmss_sign_chunks_insufficient_capacity() {
+        let params = XMSSParams::new(1, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+
+        let chunks: [&[u8]; 3] = [b"chunk 1", b"chunk 2", b"chunk 3"];
+        assert_eq!(
+            keypair.sign_chunks(&chunks).unwrap_err(),
+            XmssError::InsufficientCapacity {
+                requested: 3,
+                remaining: 2
+            }
+        );
+
+        // No leaf was consumed
+        assert_eq!(keypair.private_key().leaf_index(), 0);
+    }
+
+    #[test]
+    fn test_xmss_validate_detects_tampered_root() {
+        let params = XMSSParams::new(3, 16, 16);
+        let keypair = XMSSKeypair::generate(&params);
+        assert_eq!(keypair.validate(), Ok(()));
+
+        let restored = XMSSKeypair::restore(&params, keypair.private_key().export_state());
+        assert_eq!(restored.validate(), Ok(()));
+
+        let mut state = keypair.private_key().export_state();
+        state.root[0] ^= 0x01;
+        let tampered = XMSSKeypair::restore(&params, state);
+        assert_eq!(tampered.validate(), Err(XmssError::RootInconsistent));
+    }
+
+    #[test]
+    fn test_xmss_generic_signer() {
+        fn sign_all<S: Signer<Message = [u8]>>(
+            signer: &mut S,
+            messages: &[&[u8]],
+        ) -> Result<Vec<S::Signature>, S::Error> {
+            messages.iter().map(|m| signer.sign(m)).collect()
+        }
+
+        let params = XMSSParams::new(1, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let messages: [&[u8]; 2] = [b"first", b"second"];
+
+        let signatures = sign_all(&mut keypair, &messages).unwrap();
+        assert!(Verifier::verify(&keypair, messages[0], &signatures[0]));
+        assert!(Verifier::verify(&keypair, messages[1], &signatures[1]));
+        assert!(!Verifier::verify(&keypair, messages[1], &signatures[0]));
+
+        // The exhausted key reports an error instead of panicking
+        assert_eq!(
+            sign_all(&mut keypair, &[b"third"]).unwrap_err(),
+            XmssError::InsufficientCapacity {
+                requested: 1,
+                remaining: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_xmss_cached_tree_matches_rebuild() {
+        let params = XMSSParams::new(3, 16, 16);
+        let seed = [7u8; 96];
+        let mut cached = XMSSKeypair::generate_from_seed(&params, &seed);
+        cached.sign(b"Message 0");
+
+        // A restored keypair starts without a tree and rebuilds it on the next sign
+        let mut rebuilt = XMSSKeypair::restore(&params, cached.private_key().export_state());
+        assert!(rebuilt.tree.is_none());
+
+        for i in 1..4 {
+            let message = format!("Message {}", i);
+            let a = cached.sign(message.as_bytes());
+            let b = rebuilt.sign(message.as_bytes());
+            assert_eq!(a.to_bytes(), b.to_bytes());
+        }
+
+        let fresh = build_tree(&params, &seed[0..32], &seed[64..96]);
+        assert_eq!(rebuilt.tree.as_ref().unwrap().root(), fresh.root());
+        assert_eq!(cached.public_key().root(), fresh.root());
+    }
+
+    #[test]
+    fn test_xmss_rederive_signature() {
+        let params = XMSSParams::new(3, 16, 16);
+        let seed = [9u8; 96];
+        let mut keypair = XMSSKeypair::generate_from_seed(&params, &seed);
+
+        let signatures: Vec<_> = (0..3)
+            .map(|i| keypair.sign(format!("Message {}", i).as_bytes()))
+            .collect();
+
+        for (i, original) in signatures.iter().enumerate() {
+            let message = format!("Message {}", i);
+            let rederived = XMSSKeypair::rederive_signature(&seed, &params, i, message.as_bytes());
+            assert_eq!(rederived.to_bytes(), original.to_bytes());
+        }
+
+        // A different message at the same index gives a different signature
+        let other = XMSSKeypair::rederive_signature(&seed, &params, 0, b"other");
+        assert_ne!(other.to_bytes(), signatures[0].to_bytes());
+    }
+
+    #[test]
+    fn test_xmss_sign_and_verify_reader() {
+        use std::io::Cursor;
+
+        let params = XMSSParams::new(2, 16, 16);
+        let seed = [5u8; 96];
+        let message: Vec<u8> = (0..10 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+
+        let mut buffered = XMSSKeypair::generate_from_seed(&params, &seed);
+        let expected = buffered.sign(&message);
+
+        let mut streaming = XMSSKeypair::generate_from_seed(&params, &seed);
+        let mut reader = Cursor::new(&message);
+        let signature = streaming.sign_reader(&mut reader).unwrap();
+        assert_eq!(signature.to_bytes(), expected.to_bytes());
+        assert_eq!(streaming.private_key().leaf_index(), 1);
+
+        let public_key = streaming.public_key();
+        assert!(public_key.verify(&message, &signature, &params));
+        assert!(public_key
+            .verify_reader(&mut Cursor::new(&message), &signature, &params)
+            .unwrap());
+        assert!(!public_key
+            .verify_reader(&mut Cursor::new(&message[1..]), &signature, &params)
+            .unwrap());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parallel_generation_matches_serial() {
+        let params = XMSSParams::new(8, 16, 16);
+        let seed = [11u8; 96];
+
+        let serial = XMSSKeypair::generate_from_seed(&params, &seed);
+        let parallel = XMSSKeypair::generate_from_seed_parallel(&params, &seed);
+
+        assert_eq!(serial.public_key().root(), parallel.public_key().root());
+    }
+
+    #[test]
+    fn test_used_leaf_keys_are_refused() {
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        assert!(keypair.private_key().can_sign_index(0));
+
+        keypair.sign(b"Message 0");
+
+        let private_key = keypair.private_key();
+        assert!(!private_key.can_sign_index(0));
+        assert!(private_key.can_sign_index(1));
+        assert_eq!(
+            private_key.wots_keypair(0, &params).err(),
+            Some(XmssError::LeafAlreadyUsed { index: 0 })
+        );
+        assert!(private_key.wots_keypair(1, &params).is_ok());
+    }
+
+    #[test]
+    fn test_xmss_with_64_byte_hash() {
+        use crate::crypto::hash::HashAlg;
+
+        let params = XMSSParams::new(4, 16, 16).with_hash(HashAlg::Sha512);
+        let mut keypair = XMSSKeypair::generate(&params);
+        assert_eq!(keypair.public_key().root().len(), 64);
+
+        let message = b"64-byte hash";
+        let signature = keypair.sign(message);
+        assert_eq!(signature.randomness().len(), 64);
+        assert!(keypair.public_key().verify(message, &signature, &params));
+        assert!(!keypair
+            .public_key()
+            .verify(b"other message", &signature, &params));
+
+        let bytes = signature.to_bytes();
+        assert_eq!(bytes.len(), 4 + 64 + 16 * 64 + 4 * 64);
+        let parsed = XMSSSignature::from_bytes(&bytes, &params).unwrap();
+        assert!(keypair.public_key().verify(message, &parsed, &params));
+
+        // A SHA-256 verifier rejects the signature instead of misreading it
+        let sha256 = XMSSParams::new(4, 16, 16);
+        assert!(!keypair.public_key().verify(message, &signature, &sha256));
+    }
 }
@@ -1,11 +1,19 @@
+pub mod batch;
 pub mod core;
+pub mod error;
 pub mod keypair;
+pub mod mt;
 pub mod signature;
 pub mod tree;
+pub mod verifier;
 pub mod wots_plus;
 
+pub use self::batch::XmssSignatureBatch;
 pub use self::core::{XMSSParams, XMSSPrivateKey, XMSSPublicKey};
+pub use self::error::{XMSSSignatureError, XmssError};
 pub use self::keypair::XMSSKeypair;
+pub use self::mt::{XMSSMTKeypair, XMSSMTParams, XMSSMTSignature};
 pub use self::signature::XMSSSignature;
-pub use self::tree::{AuthPath, MerkleTree};
+pub use self::tree::{AuthPath, MerkleTree, MultiProof};
+pub use self::verifier::XmssVerifier;
 pub use self::wots_plus::WOTSPlusParams;
@@ -0,0 +1,330 @@
+// XMSS^MT: a hypertree of XMSS subtrees
+//
+// d layers of subtrees of height h give 2^(d·h) one-time keys. The layer-0
+// subtrees sign messages; each subtree on layer j > 0 signs the roots of the
+// subtrees below it, and the single subtree on the top layer provides the
+// public root. Signing only builds the d subtrees on the path to a leaf.
+
+use crate::crypto::hash::HashAlg;
+use crate::crypto::random::{OsSecureRandom, SecureRandom};
+use crate::wots::WotsSignature;
+use crate::xmss::core::{compute_wots_public_key_hash_with_params, XMSSParams, XMSSPublicKey};
+use crate::xmss::tree::{AuthPath, HashDomain, MerkleTree};
+use crate::xmss::wots_plus::WOTSPlusParams;
+use subtle::ConstantTimeEq;
+
+/// XMSS^MT parameters: per-subtree XMSS parameters and the number of layers
+#[derive(Debug, Clone)]
+pub struct XMSSMTParams {
+    subtree_params: XMSSParams,
+    layers: usize,
+}
+
+impl XMSSMTParams {
+    pub fn new(subtree_params: XMSSParams, layers: usize) -> Self {
+        assert!(layers > 0, "Number of layers must be positive");
+        assert!(
+            subtree_params.tree_height() * layers < usize::BITS as usize,
+            "Total tree height too large"
+        );
+
+        XMSSMTParams {
+            subtree_params,
+            layers,
+        }
+    }
+
+    pub fn subtree_params(&self) -> &XMSSParams {
+        &self.subtree_params
+    }
+
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    /// Total height d·h of the hypertree
+    pub fn total_tree_height(&self) -> usize {
+        self.subtree_params.tree_height() * self.layers
+    }
+
+    pub fn max_signatures(&self) -> usize {
+        1 << self.total_tree_height()
+    }
+}
+
+/// XMSS^MT signature: one WOTS signature and authentication path per layer,
+/// from the layer-0 subtree up to the top subtree
+#[derive(Debug, Clone)]
+pub struct XMSSMTSignature {
+    index: usize,
+    randomness: Vec<u8>,
+    wots_signatures: Vec<WotsSignature>,
+    auth_paths: Vec<AuthPath>,
+}
+
+impl XMSSMTSignature {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn randomness(&self) -> &[u8] {
+        &self.randomness
+    }
+
+    pub fn wots_signatures(&self) -> &[WotsSignature] {
+        &self.wots_signatures
+    }
+
+    pub fn auth_paths(&self) -> &[AuthPath] {
+        &self.auth_paths
+    }
+
+    /// Verify against the top root and public seed
+    pub fn verify(
+        &self,
+        message: &[u8],
+        public_key: &XMSSPublicKey,
+        params: &XMSSMTParams,
+    ) -> bool {
+        let h = params.subtree_params().tree_height();
+
+        if self.index >= params.max_signatures()
+            || self.wots_signatures.len() != params.layers()
+            || self.auth_paths.len() != params.layers()
+            || self.auth_paths.iter().any(|path| path.nodes().len() != h)
+        {
+            return false;
+        }
+
+        let hasher = params.subtree_params().hash();
+        let mut node = message_digest(
+            &hasher,
+            &self.randomness,
+            public_key.root(),
+            self.index,
+            message,
+        );
+        let mut tree_index = self.index;
+
+        let layers = self.wots_signatures.iter().zip(&self.auth_paths);
+        for (layer, (wots_signature, auth_path)) in layers.enumerate() {
+            let leaf_index = tree_index & ((1 << h) - 1);
+            tree_index >>= h;
+
+            // Recover the WOTS leaf from the signed node, then climb the subtree
+            let address = wots_address(layer, tree_index, leaf_index);
+            let leaf = match compute_wots_public_key_hash_with_params(
+                &node,
+                wots_signature,
+                &hasher,
+                params.subtree_params(),
+                public_key.public_seed(),
+                &address,
+            ) {
+                Some(leaf) => leaf,
+                None => return false,
+            };
+            node = auth_path.compute_root(&leaf, leaf_index, public_key.public_seed(), &hasher);
+        }
+
+        node.ct_eq(public_key.root()).into()
+    }
+}
+
+pub struct XMSSMTKeypair {
+    public_key: XMSSPublicKey,
+    params: XMSSMTParams,
+    index: usize,
+    sk_seed: Vec<u8>,
+    sk_prf: Vec<u8>,
+}
+
+impl XMSSMTKeypair {
+    pub fn generate(params: &XMSSMTParams) -> Self {
+        let mut rng = OsSecureRandom::new();
+        let seed = rng.random_bytes(96); // 32 + 32 + 32
+
+        Self::generate_from_seed(params, &seed)
+    }
+
+    pub fn generate_from_seed(params: &XMSSMTParams, seed: &[u8]) -> Self {
+        assert_eq!(seed.len(), 96, "Seed must be 96 bytes");
+
+        let sk_seed = seed[0..32].to_vec();
+        let sk_prf = seed[32..64].to_vec();
+        let public_seed = seed[64..96].to_vec();
+
+        let top_tree = build_subtree(params, &sk_seed, &public_seed, params.layers() - 1, 0);
+        let public_key = XMSSPublicKey::for_params(
+            top_tree.root().to_vec(),
+            public_seed,
+            params.subtree_params(),
+        );
+
+        XMSSMTKeypair {
+            public_key,
+            params: params.clone(),
+            index: 0,
+            sk_seed,
+            sk_prf,
+        }
+    }
+
+    pub fn public_key(&self) -> &XMSSPublicKey {
+        &self.public_key
+    }
+
+    pub fn params(&self) -> &XMSSMTParams {
+        &self.params
+    }
+
+    /// Index of the next one-time key
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn sign(&mut self, message: &[u8]) -> XMSSMTSignature {
+        let index = self.index;
+        if index >= self.params.max_signatures() {
+            panic!("XMSS^MT key exhausted");
+        }
+
+        let hasher = self.params.subtree_params().hash();
+        let h = self.params.subtree_params().tree_height();
+        let wots_params = WOTSPlusParams::from_xmss_params(self.params.subtree_params());
+        let public_seed = self.public_key.public_seed().to_vec();
+
+        // Compute PRF(SK_PRF, idx_sig || M)
+        let mut prf = HashDomain::MessagePrf.hasher(&hasher);
+        prf.update(&self.sk_prf);
+        prf.update(&(index as u64).to_be_bytes());
+        prf.update(message);
+        let randomness = prf.finalize();
+
+        let mut node = message_digest(&hasher, &randomness, self.public_key.root(), index, message);
+        let mut tree_index = index;
+        let mut wots_signatures = Vec::with_capacity(self.params.layers());
+        let mut auth_paths = Vec::with_capacity(self.params.layers());
+
+        for layer in 0..self.params.layers() {
+            let leaf_index = tree_index & ((1 << h) - 1);
+            tree_index >>= h;
+
+            let address = wots_address(layer, tree_index, leaf_index);
+            let wots_keypair = wots_params.generate_keypair(&self.sk_seed, &public_seed, &address);
+            wots_signatures.push(wots_keypair.sign(&node));
+
+            let tree = build_subtree(&self.params, &self.sk_seed, &public_seed, layer, tree_index);
+            auth_paths.push(tree.authentication_path(leaf_index));
+
+            // The next layer signs this subtree's root
+            node = tree.root().to_vec();
+        }
+
+        self.index += 1;
+
+        XMSSMTSignature {
+            index,
+            randomness,
+            wots_signatures,
+            auth_paths,
+        }
+    }
+}
+
+/// Hash(r || root || idx_sig || M)
+fn message_digest(
+    hasher: &HashAlg,
+    randomness: &[u8],
+    root: &[u8],
+    index: usize,
+    message: &[u8],
+) -> Vec<u8> {
+    let mut digest = HashDomain::MessageDigest.hasher(hasher);
+    digest.update(randomness);
+    digest.update(root);
+    digest.update(&(index as u64).to_be_bytes());
+    digest.update(message);
+    digest.finalize()
+}
+
+/// WOTS key address: layer || tree index || leaf index
+fn wots_address(layer: usize, tree_index: usize, leaf_index: usize) -> Vec<u8> {
+    let mut address = Vec::with_capacity(16);
+    address.extend_from_slice(&(layer as u32).to_be_bytes());
+    address.extend_from_slice(&(tree_index as u64).to_be_bytes());
+    address.extend_from_slice(&(leaf_index as u32).to_be_bytes());
+    address
+}
+
+fn build_subtree(
+    params: &XMSSMTParams,
+    sk_seed: &[u8],
+    public_seed: &[u8],
+    layer: usize,
+    tree_index: usize,
+) -> MerkleTree {
+    let num_leaves = 1 << params.subtree_params().tree_height();
+    let wots_params = WOTSPlusParams::from_xmss_params(params.subtree_params());
+
+    let leaves: Vec<Vec<u8>> = (0..num_leaves)
+        .map(|leaf_index| {
+            let address = wots_address(layer, tree_index, leaf_index);
+            wots_params
+                .generate_keypair(sk_seed, public_seed, &address)
+                .public_key_hash()
+        })
+        .collect();
+
+    MerkleTree::build(&leaves, public_seed, &params.subtree_params().hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xmss_mt_signs_beyond_single_tree_capacity() {
+        // Two layers of height-2 subtrees: 16 signatures, 4 per subtree
+        let params = XMSSMTParams::new(XMSSParams::new(2, 16, 16), 2);
+        let mut keypair = XMSSMTKeypair::generate(&params);
+        assert_eq!(params.max_signatures(), 16);
+
+        let single_tree_capacity = 1 << params.subtree_params().tree_height();
+        for i in 0..single_tree_capacity + 2 {
+            let message = format!("Message {}", i);
+            let signature = keypair.sign(message.as_bytes());
+            assert_eq!(signature.index(), i);
+            assert!(signature.verify(message.as_bytes(), keypair.public_key(), &params));
+            assert!(!signature.verify(b"other message", keypair.public_key(), &params));
+        }
+
+        assert_eq!(keypair.index(), single_tree_capacity + 2);
+    }
+
+    #[test]
+    fn test_xmss_mt_deterministic_root() {
+        let params = XMSSMTParams::new(XMSSParams::new(2, 16, 16), 2);
+        let seed = [7u8; 96];
+
+        let a = XMSSMTKeypair::generate_from_seed(&params, &seed);
+        let b = XMSSMTKeypair::generate_from_seed(&params, &seed);
+        assert_eq!(a.public_key().root(), b.public_key().root());
+    }
+
+    #[test]
+    fn test_xmss_mt_with_64_byte_hash() {
+        let subtree_params = XMSSParams::new(2, 16, 16).with_hash(HashAlg::Sha512);
+        let params = XMSSMTParams::new(subtree_params, 2);
+        let mut keypair = XMSSMTKeypair::generate(&params);
+        assert_eq!(keypair.public_key().root().len(), 64);
+
+        for i in 0..5 {
+            let message = format!("Message {}", i);
+            let signature = keypair.sign(message.as_bytes());
+            assert_eq!(signature.randomness().len(), 64);
+            assert!(signature.verify(message.as_bytes(), keypair.public_key(), &params));
+            assert!(!signature.verify(b"other message", keypair.public_key(), &params));
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::wots::WotsSignature;
 use crate::xmss::core::XMSSParams;
+use crate::xmss::error::XMSSSignatureError;
 use crate::xmss::tree::AuthPath;
 
 #[derive(Debug, Clone)]
@@ -16,8 +17,20 @@ impl XMSSSignature {
         randomness: Vec<u8>,
         wots_signature: WotsSignature,
         auth_path: AuthPath,
+        params: &XMSSParams,
     ) -> Self {
-        assert_eq!(randomness.len(), 32, "Randomness must be 32 bytes");
+        let n = params.hash_size();
+        assert_eq!(randomness.len(), n, "Randomness must be {} bytes", n);
+        assert!(
+            wots_signature.chains().iter().all(|chain| chain.len() == n),
+            "WOTS chains must be {} bytes",
+            n
+        );
+        assert!(
+            auth_path.nodes().iter().all(|node| node.len() == n),
+            "Authentication path nodes must be {} bytes",
+            n
+        );
 
         XMSSSignature {
             leaf_index,
@@ -61,20 +74,23 @@ impl XMSSSignature {
         bytes
     }
 
-    pub fn from_bytes(bytes: &[u8], params: &XMSSParams) -> Result<Self, String> {
-        let hash_size = 32; // SHA256 output size
+    /// Parse a signature serialized by `to_bytes`
+    /// The buffer must be exactly 4 + n + len·n + h·n bytes for `params`,
+    /// where n is the hash output size, and the leaf index must lie within
+    /// the tree.
+    pub fn from_bytes(bytes: &[u8], params: &XMSSParams) -> Result<Self, XMSSSignatureError> {
+        let hash_size = params.hash_size();
         let wots_chains = params.len(); // Number of WOTS chains
         let tree_height = params.tree_height();
 
         // Calculate expected size
-        let expected_size = 4 + 32 + (wots_chains * hash_size) + (tree_height * hash_size);
+        let expected_size = 4 + hash_size + (wots_chains * hash_size) + (tree_height * hash_size);
 
         if bytes.len() != expected_size {
-            return Err(format!(
-                "Invalid signature length: expected {}, got {}",
-                expected_size,
-                bytes.len()
-            ));
+            return Err(XMSSSignatureError::InvalidLength {
+                expected: expected_size,
+                actual: bytes.len(),
+            });
         }
 
         let mut offset = 0;
@@ -88,9 +104,17 @@ impl XMSSSignature {
         ]) as usize;
         offset += 4;
 
+        let max_leaves = 1 << tree_height;
+        if leaf_index >= max_leaves {
+            return Err(XMSSSignatureError::LeafIndexOutOfRange {
+                index: leaf_index,
+                max: max_leaves,
+            });
+        }
+
         // Parse randomness
-        let randomness = bytes[offset..offset + 32].to_vec();
-        offset += 32;
+        let randomness = bytes[offset..offset + hash_size].to_vec();
+        offset += hash_size;
 
         // Parse WOTS signature chains
         let mut wots_chains = Vec::with_capacity(wots_chains);
@@ -152,4 +176,61 @@ mod tests {
         assert_eq!(signature.auth_path().nodes().len(), 4);
         assert!(signature.wots_signature().chains().len() > 0);
     }
+
+    #[test]
+    fn test_xmss_signature_new_checks_hash_size() {
+        use crate::crypto::hash::HashAlg;
+
+        let params = XMSSParams::new(2, 16, 2).with_hash(HashAlg::Sha512);
+        let wots = WotsSignature::from_chains(vec![vec![0u8; 64]; 2]);
+        let path = AuthPath::new(vec![vec![0u8; 64]; 2]);
+        let signature = XMSSSignature::new(1, vec![0u8; 64], wots, path, &params);
+        assert_eq!(signature.randomness().len(), 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "Randomness must be 64 bytes")]
+    fn test_xmss_signature_new_rejects_short_randomness() {
+        use crate::crypto::hash::HashAlg;
+
+        let params = XMSSParams::new(2, 16, 2).with_hash(HashAlg::Sha512);
+        let wots = WotsSignature::from_chains(vec![vec![0u8; 64]; 2]);
+        XMSSSignature::new(1, vec![0u8; 32], wots, AuthPath::new(vec![]), &params);
+    }
+
+    #[test]
+    fn test_xmss_signature_from_bytes_rejects_bad_length() {
+        let params = XMSSParams::new(2, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let bytes = keypair.sign(b"Length test").to_bytes();
+        let expected = 4 + 32 + 16 * 32 + 2 * 32;
+        assert_eq!(bytes.len(), expected);
+
+        let short = &bytes[..bytes.len() - 1];
+        assert_eq!(
+            XMSSSignature::from_bytes(short, &params).unwrap_err(),
+            XMSSSignatureError::InvalidLength {
+                expected,
+                actual: expected - 1
+            }
+        );
+
+        let mut long = bytes.clone();
+        long.push(0);
+        assert_eq!(
+            XMSSSignature::from_bytes(&long, &params).unwrap_err(),
+            XMSSSignatureError::InvalidLength {
+                expected,
+                actual: expected + 1
+            }
+        );
+
+        // A leaf index past the last leaf is rejected as well
+        let mut bad_index = bytes;
+        bad_index[..4].copy_from_slice(&4u32.to_be_bytes());
+        assert_eq!(
+            XMSSSignature::from_bytes(&bad_index, &params).unwrap_err(),
+            XMSSSignatureError::LeafIndexOutOfRange { index: 4, max: 4 }
+        );
+    }
 }
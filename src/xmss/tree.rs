@@ -1,4 +1,33 @@
-use crate::crypto::hash::HashFunction;
+use crate::crypto::hash::{HashFunction, IncrementalHasher};
+use subtle::ConstantTimeEq;
+
+/// Domain separation tag prefixed to every XMSS hash input
+/// Keeps leaves, tree nodes and message hashes in disjoint input spaces, in
+/// the spirit of the RFC 8391 hash function addressing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashDomain {
+    /// WOTS public key compression into a leaf
+    Leaf = 0x00,
+    /// Internal Merkle tree node
+    TreeNode = 0x01,
+    /// PRF(SK_PRF, idx || M) deriving the signature randomness
+    MessagePrf = 0x02,
+    /// H(r || root || idx || M), the digest the WOTS key signs
+    MessageDigest = 0x03,
+    /// WOTS+ bitmask for one chain step
+    ChainMask = 0x04,
+    /// WOTS+ chain step on a masked value
+    ChainStep = 0x05,
+}
+
+impl HashDomain {
+    /// Start a hash computation that has already absorbed this domain's tag
+    pub(crate) fn hasher<H: HashFunction + ?Sized>(self, hash: &H) -> Box<dyn IncrementalHasher> {
+        let mut state = hash.hasher();
+        state.update(&[self as u8]);
+        state
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct MerkleTree {
@@ -45,6 +74,20 @@ impl MerkleTree {
         self.height
     }
 
+    pub fn leaf_count(&self) -> usize {
+        self.nodes[0].len()
+    }
+
+    pub fn leaf(&self, index: usize) -> &[u8] {
+        &self.nodes[0][index]
+    }
+
+    /// Returns the node at `index` on `level`, where level 0 holds the leaves
+    /// and level `height` holds the root.
+    pub fn node(&self, level: usize, index: usize) -> &[u8] {
+        &self.nodes[level][index]
+    }
+
     pub fn authentication_path(&self, leaf_index: usize) -> AuthPath {
         let mut auth_nodes = Vec::new();
         let mut index = leaf_index;
@@ -57,6 +100,35 @@ impl MerkleTree {
 
         AuthPath::new(auth_nodes)
     }
+
+    /// Builds a single proof for several leaves, including each internal
+    /// node only once. Siblings that can be recomputed from other proven
+    /// leaves are omitted.
+    pub fn multi_authentication_path(&self, indices: &[usize]) -> MultiProof {
+        let mut known: Vec<usize> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+
+        let mut nodes = Vec::new();
+        for h in 0..self.height {
+            let mut parents: Vec<usize> = Vec::with_capacity(known.len());
+            for &index in &known {
+                let sibling_index = index ^ 1;
+                if known.binary_search(&sibling_index).is_err() {
+                    nodes.push(self.nodes[h][sibling_index].clone());
+                }
+                if parents.last() != Some(&(index >> 1)) {
+                    parents.push(index >> 1);
+                }
+            }
+            known = parents;
+        }
+
+        MultiProof {
+            nodes,
+            height: self.height,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -80,21 +152,187 @@ impl AuthPath {
         public_seed: &[u8],
         hasher: &H,
     ) -> Vec<u8> {
-        let mut node = leaf.to_vec();
+        self.compute_root_trace(leaf, leaf_index, public_seed, hasher)
+            .pop()
+            .expect("trace contains at least the leaf")
+    }
+
+    /// Nodes on the path from the leaf (level 0) up to the computed root
+    /// Entry `h` is the node at level h, so the trace has `nodes().len() + 1`
+    /// entries; comparing it with a tree's nodes locates where a path diverges.
+    pub fn compute_root_trace<H: HashFunction>(
+        &self,
+        leaf: &[u8],
+        leaf_index: usize,
+        public_seed: &[u8],
+        hasher: &H,
+    ) -> Vec<Vec<u8>> {
+        let mut trace = Vec::with_capacity(self.nodes.len() + 1);
+        trace.push(leaf.to_vec());
         let mut index = leaf_index;
 
         for (h, auth_node) in self.nodes.iter().enumerate() {
+            let node = &trace[h];
             let (left, right) = if index & 1 == 0 {
-                (&node, auth_node)
+                (node, auth_node)
             } else {
-                (auth_node, &node)
+                (auth_node, node)
             };
 
-            node = hash_tree_node(hasher, public_seed, h, index >> 1, left, right);
+            let parent = hash_tree_node(hasher, public_seed, h, index >> 1, left, right);
+            trace.push(parent);
             index >>= 1;
         }
 
-        node
+        trace
+    }
+}
+
+/// Authentication data for several leaves of the same tree
+/// Nodes are stored level by level, and within a level in increasing
+/// index order of the node they complete.
+#[derive(Debug, Clone)]
+pub struct MultiProof {
+    nodes: Vec<Vec<u8>>,
+    height: usize,
+}
+
+impl MultiProof {
+    /// Assemble a multi-proof from the individual authentication paths
+    /// `paths[i]` authenticates leaf `indices[i]`. Produces the same nodes as
+    /// `MerkleTree::multi_authentication_path`; returns `None` if the inputs
+    /// are empty, of different lengths, or the paths have different heights.
+    pub fn from_auth_paths(indices: &[usize], paths: &[AuthPath]) -> Option<Self> {
+        let height = paths.first()?.nodes.len();
+        if indices.len() != paths.len() || paths.iter().any(|p| p.nodes.len() != height) {
+            return None;
+        }
+
+        let mut known: Vec<usize> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+
+        let mut nodes = Vec::new();
+        for h in 0..height {
+            let mut parents: Vec<usize> = Vec::with_capacity(known.len());
+            for &index in &known {
+                if known.binary_search(&(index ^ 1)).is_err() {
+                    // Any path through this node carries its sibling at level h
+                    let (_, path) = indices
+                        .iter()
+                        .zip(paths)
+                        .find(|(&leaf, _)| leaf >> h == index)?;
+                    nodes.push(path.nodes[h].clone());
+                }
+                if parents.last() != Some(&(index >> 1)) {
+                    parents.push(index >> 1);
+                }
+            }
+            known = parents;
+        }
+
+        Some(MultiProof { nodes, height })
+    }
+
+    /// Number of nodes a multi-proof for `indices` in a tree of `height` holds
+    pub fn node_count(indices: &[usize], height: usize) -> usize {
+        let mut known: Vec<usize> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+
+        let mut count = 0;
+        for _ in 0..height {
+            count += known
+                .iter()
+                .filter(|&&index| known.binary_search(&(index ^ 1)).is_err())
+                .count();
+            known = known.iter().map(|&index| index >> 1).collect();
+            known.dedup();
+        }
+        count
+    }
+
+    pub fn from_nodes(nodes: Vec<Vec<u8>>, height: usize) -> Self {
+        MultiProof { nodes, height }
+    }
+
+    pub fn nodes(&self) -> &[Vec<u8>] {
+        &self.nodes
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn verify<H: HashFunction>(
+        &self,
+        leaves: &[Vec<u8>],
+        indices: &[usize],
+        root: &[u8],
+        public_seed: &[u8],
+        hasher: &H,
+    ) -> bool {
+        match self.compute_root(leaves, indices, public_seed, hasher) {
+            Some(computed_root) => computed_root.ct_eq(root).into(),
+            None => false,
+        }
+    }
+
+    fn compute_root<H: HashFunction>(
+        &self,
+        leaves: &[Vec<u8>],
+        indices: &[usize],
+        public_seed: &[u8],
+        hasher: &H,
+    ) -> Option<Vec<u8>> {
+        if leaves.is_empty() || leaves.len() != indices.len() {
+            return None;
+        }
+
+        let mut level: Vec<(usize, Vec<u8>)> = indices
+            .iter()
+            .copied()
+            .zip(leaves.iter().cloned())
+            .collect();
+        level.sort_by_key(|(index, _)| *index);
+
+        let num_leaves = 1usize << self.height;
+        if level.windows(2).any(|pair| pair[0].0 == pair[1].0)
+            || level.iter().any(|(index, _)| *index >= num_leaves)
+        {
+            return None;
+        }
+
+        let mut proof_nodes = self.nodes.iter();
+        for h in 0..self.height {
+            let mut parents = Vec::with_capacity(level.len());
+            let mut k = 0;
+            while k < level.len() {
+                let (index, ref node) = level[k];
+                let parent = if index & 1 == 0 {
+                    let right = if level.get(k + 1).map(|(i, _)| *i) == Some(index + 1) {
+                        k += 1;
+                        &level[k].1
+                    } else {
+                        proof_nodes.next()?
+                    };
+                    hash_tree_node(hasher, public_seed, h, index >> 1, node, right)
+                } else {
+                    let left = proof_nodes.next()?;
+                    hash_tree_node(hasher, public_seed, h, index >> 1, left, node)
+                };
+                parents.push((index >> 1, parent));
+                k += 1;
+            }
+            level = parents;
+        }
+
+        // Every supplied node must have been consumed
+        if proof_nodes.next().is_some() {
+            return None;
+        }
+
+        level.pop().map(|(_, node)| node)
     }
 }
 
@@ -106,15 +344,13 @@ fn hash_tree_node<H: HashFunction>(
     left: &[u8],
     right: &[u8],
 ) -> Vec<u8> {
-    let mut data = Vec::new();
-    data.push(0x01);
-    data.extend_from_slice(public_seed);
-    data.extend_from_slice(&(height as u32).to_be_bytes());
-    data.extend_from_slice(&(index as u32).to_be_bytes());
-    data.extend_from_slice(left);
-    data.extend_from_slice(right);
-
-    hasher.hash(&data)
+    let mut state = HashDomain::TreeNode.hasher(hasher);
+    state.update(public_seed);
+    state.update(&(height as u32).to_be_bytes());
+    state.update(&(index as u32).to_be_bytes());
+    state.update(left);
+    state.update(right);
+    state.finalize()
 }
 
 #[cfg(test)]
@@ -177,4 +413,196 @@ mod tests {
             assert_eq!(computed_root, tree.root());
         }
     }
+
+    #[test]
+    fn test_merkle_tree_accessors() {
+        let hasher = SHA256::new();
+        let leaves: Vec<Vec<u8>> = (0..8)
+            .map(|i| hasher.hash(format!("leaf_{}", i).as_bytes()))
+            .collect();
+
+        let tree = MerkleTree::build(&leaves, &[0u8; 32], &hasher);
+
+        assert_eq!(tree.leaf_count(), 8);
+        assert_eq!(tree.leaf(0), leaves[0].as_slice());
+        assert_eq!(tree.leaf(7), leaves[7].as_slice());
+        assert_eq!(tree.node(0, 3), leaves[3].as_slice());
+        assert_eq!(tree.node(tree.height(), 0), tree.root());
+    }
+
+    #[test]
+    fn test_multi_authentication_path() {
+        let hasher = SHA256::new();
+        let leaves: Vec<Vec<u8>> = (0..8)
+            .map(|i| hasher.hash(format!("leaf_{}", i).as_bytes()))
+            .collect();
+
+        let public_seed = [0u8; 32];
+        let tree = MerkleTree::build(&leaves, &public_seed, &hasher);
+
+        let indices = [0, 1, 2];
+        let proof = tree.multi_authentication_path(&indices);
+        let proven: Vec<Vec<u8>> = indices.iter().map(|&i| leaves[i].clone()).collect();
+
+        assert!(proof.verify(&proven, &indices, tree.root(), &public_seed, &hasher));
+
+        // Three independent paths carry 3 * height nodes
+        let independent: usize = indices
+            .iter()
+            .map(|&i| tree.authentication_path(i).nodes().len())
+            .sum();
+        assert!(proof.nodes().len() < independent);
+
+        // A wrong leaf or wrong index fails
+        let mut tampered = proven.clone();
+        tampered[2] = leaves[5].clone();
+        assert!(!proof.verify(&tampered, &indices, tree.root(), &public_seed, &hasher));
+        assert!(!proof.verify(&proven, &[0, 1, 3], tree.root(), &public_seed, &hasher));
+    }
+
+    #[test]
+    fn test_compute_root_trace() {
+        let hasher = SHA256::new();
+        let public_seed = vec![0u8; 32];
+        let leaves: Vec<Vec<u8>> = (0..8u8).map(|i| hasher.hash(&[i])).collect();
+        let tree = MerkleTree::build(&leaves, &public_seed, &hasher);
+
+        let leaf_index = 5;
+        let path = tree.authentication_path(leaf_index);
+        let trace = path.compute_root_trace(&leaves[leaf_index], leaf_index, &public_seed, &hasher);
+
+        assert_eq!(trace.len(), 4);
+        for (level, node) in trace.iter().enumerate() {
+            assert_eq!(node.as_slice(), tree.node(level, leaf_index >> level));
+        }
+        assert_eq!(
+            path.compute_root(&leaves[leaf_index], leaf_index, &public_seed, &hasher),
+            tree.root()
+        );
+
+        // A corrupted sibling at level 1 first shows up in the level-2 node
+        let mut nodes = path.nodes().to_vec();
+        nodes[1][0] ^= 0xff;
+        let trace = AuthPath::new(nodes).compute_root_trace(
+            &leaves[leaf_index],
+            leaf_index,
+            &public_seed,
+            &hasher,
+        );
+        let diverged =
+            (0..trace.len()).find(|&level| trace[level] != tree.node(level, leaf_index >> level));
+        assert_eq!(diverged, Some(2));
+    }
+
+    #[test]
+    fn test_multi_proof_from_auth_paths() {
+        let hasher = SHA256::new();
+        let public_seed = vec![0u8; 32];
+        let leaves: Vec<Vec<u8>> = (0..16u8).map(|i| hasher.hash(&[i])).collect();
+        let tree = MerkleTree::build(&leaves, &public_seed, &hasher);
+
+        let indices = [3, 4, 5, 11];
+        let paths: Vec<AuthPath> = indices
+            .iter()
+            .map(|&i| tree.authentication_path(i))
+            .collect();
+
+        let proof = MultiProof::from_auth_paths(&indices, &paths).unwrap();
+        assert_eq!(
+            proof.nodes(),
+            tree.multi_authentication_path(&indices).nodes()
+        );
+
+        let proven: Vec<Vec<u8>> = indices.iter().map(|&i| leaves[i].clone()).collect();
+        assert!(proof.verify(&proven, &indices, tree.root(), &public_seed, &hasher));
+
+        assert!(MultiProof::from_auth_paths(&indices[..2], &paths).is_none());
+    }
+
+    #[test]
+    fn test_leaf_and_node_hashes_are_domain_separated() {
+        let hasher = SHA256::new();
+        let (left, right) = (vec![0xaa; 32], vec![0x55; 32]);
+
+        let leaf = crate::xmss::core::hash_wots_public_key(&[left.clone(), right.clone()], &hasher);
+        let node = hash_tree_node(&hasher, &[], 0, 0, &left, &right);
+        assert_ne!(leaf, node);
+
+        // The tags alone separate identical inputs
+        let tagged = |domain: HashDomain| {
+            let mut state = domain.hasher(&hasher);
+            state.update(&left);
+            state.update(&right);
+            state.finalize()
+        };
+        assert_eq!(leaf, tagged(HashDomain::Leaf));
+        assert_ne!(tagged(HashDomain::Leaf), tagged(HashDomain::TreeNode));
+        assert_ne!(leaf, hasher.hash(&[left, right].concat()));
+    }
+
+    /// 64-byte test hash: SHA-256(0 || m) || SHA-256(1 || m)
+    struct MockWideHash;
+
+    struct MockWideHasher(Vec<u8>);
+
+    impl crate::crypto::hash::IncrementalHasher for MockWideHasher {
+        fn update(&mut self, data: &[u8]) {
+            self.0.extend_from_slice(data);
+        }
+
+        fn finalize(self: Box<Self>) -> Vec<u8> {
+            let mut output = SHA256::new().hash(&[&[0u8][..], &self.0].concat());
+            output.extend(SHA256::new().hash(&[&[1u8][..], &self.0].concat()));
+            output
+        }
+    }
+
+    impl HashFunction for MockWideHash {
+        fn hasher(&self) -> Box<dyn crate::crypto::hash::IncrementalHasher> {
+            Box::new(MockWideHasher(Vec::new()))
+        }
+
+        fn output_size(&self) -> usize {
+            64
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_with_mock_64_byte_hash() {
+        use crate::crypto::hash::HashAlg;
+        use crate::xmss::{XMSSParams, XMSSPublicKey};
+
+        let hasher = MockWideHash;
+        let public_seed = [3u8; 32];
+        let leaves: Vec<Vec<u8>> = (0..16)
+            .map(|i| hasher.hash(format!("leaf_{}", i).as_bytes()))
+            .collect();
+
+        let tree = MerkleTree::build(&leaves, &public_seed, &hasher);
+        assert_eq!(tree.height(), 4);
+        assert_eq!(tree.root().len(), 64);
+
+        for (leaf_idx, leaf) in leaves.iter().enumerate() {
+            let auth_path = tree.authentication_path(leaf_idx);
+            assert!(auth_path.nodes().iter().all(|node| node.len() == 64));
+            let computed_root = auth_path.compute_root(leaf, leaf_idx, &public_seed, &hasher);
+            assert_eq!(computed_root, tree.root());
+            assert_ne!(
+                auth_path.compute_root(
+                    &leaves[(leaf_idx + 1) % 16],
+                    leaf_idx,
+                    &public_seed,
+                    &hasher
+                ),
+                tree.root()
+            );
+        }
+
+        // The public key is sized by the hash output, not a fixed 32 bytes
+        let params = XMSSParams::new(4, 16, 16).with_hash(HashAlg::Sha512);
+        assert_eq!(params.hash_size(), hasher.output_size());
+        let public_key =
+            XMSSPublicKey::for_params(tree.root().to_vec(), public_seed.to_vec(), &params);
+        assert_eq!(public_key.root(), tree.root());
+    }
 }
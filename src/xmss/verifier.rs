@@ -0,0 +1,132 @@
+use crate::xmss::core::{XMSSParams, XMSSPublicKey};
+use crate::xmss::signature::XMSSSignature;
+use std::collections::HashMap;
+use subtle::ConstantTimeEq;
+
+/// XMSS verifier that remembers authenticated leaves
+/// Once a leaf and authentication path have been checked against the root,
+/// a later signature at the same index that reconstructs the same leaf with
+/// the same path (e.g. a retransmission) is accepted without Merkle hashing.
+/// The WOTS part is always recomputed.
+pub struct XmssVerifier {
+    public_key: XMSSPublicKey,
+    params: XMSSParams,
+    cache: HashMap<usize, AuthenticatedLeaf>,
+    merkle_hashes: usize,
+}
+
+struct AuthenticatedLeaf {
+    leaf: Vec<u8>,
+    auth_nodes: Vec<Vec<u8>>,
+}
+
+impl XmssVerifier {
+    pub fn new(public_key: XMSSPublicKey, params: XMSSParams) -> Self {
+        XmssVerifier {
+            public_key,
+            params,
+            cache: HashMap::new(),
+            merkle_hashes: 0,
+        }
+    }
+
+    pub fn public_key(&self) -> &XMSSPublicKey {
+        &self.public_key
+    }
+
+    /// Replace the public key, invalidating all cached leaves
+    pub fn set_public_key(&mut self, public_key: XMSSPublicKey) {
+        self.public_key = public_key;
+        self.cache.clear();
+    }
+
+    /// Number of Merkle node hashes computed so far
+    pub fn merkle_hashes(&self) -> usize {
+        self.merkle_hashes
+    }
+
+    pub fn verify(&mut self, message: &[u8], signature: &XMSSSignature) -> bool {
+        let root = self.public_key.root().to_vec();
+        let leaf = match self
+            .public_key
+            .compute_leaf(&root, message, signature, &self.params)
+        {
+            Some(leaf) => leaf,
+            None => return false,
+        };
+
+        let auth_nodes = signature.auth_path().nodes();
+        if let Some(cached) = self.cache.get(&signature.leaf_index()) {
+            if cached.leaf == leaf && cached.auth_nodes == auth_nodes {
+                return true;
+            }
+        }
+
+        let computed_root = signature.auth_path().compute_root(
+            &leaf,
+            signature.leaf_index(),
+            self.public_key.public_seed(),
+            &self.params.hash(),
+        );
+        self.merkle_hashes += auth_nodes.len();
+
+        let valid: bool = computed_root.ct_eq(&root).into();
+        if valid {
+            self.cache.insert(
+                signature.leaf_index(),
+                AuthenticatedLeaf {
+                    leaf,
+                    auth_nodes: auth_nodes.to_vec(),
+                },
+            );
+        }
+
+        valid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xmss::XMSSKeypair;
+
+    #[test]
+    fn test_verifier_caches_authenticated_leaf() {
+        let params = XMSSParams::new(3, 16, 16);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let message = b"retransmitted";
+        let signature = keypair.sign(message);
+
+        let mut verifier = XmssVerifier::new(keypair.public_key().clone(), params.clone());
+
+        assert!(verifier.verify(message, &signature));
+        assert_eq!(verifier.merkle_hashes(), 3);
+
+        // The same signature at index 0 reuses the cached Merkle state
+        assert!(verifier.verify(message, &signature));
+        assert_eq!(verifier.merkle_hashes(), 3);
+
+        // A wrong message reconstructs a different leaf and is rejected
+        assert!(!verifier.verify(b"other message", &signature));
+
+        // Changing the public key drops the cache
+        let other = XMSSKeypair::generate(&params);
+        verifier.set_public_key(other.public_key().clone());
+        assert!(!verifier.verify(message, &signature));
+        assert_eq!(verifier.merkle_hashes(), 9);
+    }
+
+    #[test]
+    fn test_verifier_uses_selected_hash() {
+        use crate::crypto::hash::HashAlg;
+
+        let params = XMSSParams::new(2, 16, 16).with_hash(HashAlg::Sha512);
+        let mut keypair = XMSSKeypair::generate(&params);
+        let signature = keypair.sign(b"message");
+        assert!(keypair.public_key().verify(b"message", &signature, &params));
+
+        let mut verifier = XmssVerifier::new(keypair.public_key().clone(), params);
+        assert!(verifier.verify(b"message", &signature));
+        assert!(!verifier.verify(b"other message", &signature));
+    }
+}
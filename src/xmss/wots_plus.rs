@@ -1,9 +1,13 @@
-use crate::crypto::hash::{HashFunction, SHA256};
+use crate::core::encoding::EncodingScheme;
+use crate::core::mapping::MappingError;
+use crate::crypto::hash::HashFunction;
 use crate::schemes::tl1c::TL1C;
 use crate::schemes::tlfc::TLFC;
 use crate::schemes::tsl::{TSLConfig, TSL};
 use crate::wots::{WotsKeypair, WotsParams, WotsSignature};
 use crate::xmss::core::XMSSParams;
+use crate::xmss::tree::HashDomain;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
 
 #[derive(Debug, Clone)]
 pub struct WOTSPlusParams {
@@ -23,7 +27,8 @@ impl WOTSPlusParams {
             }
         } else {
             WotsParams::new(xmss_params.winternitz_parameter(), xmss_params.len())
-        };
+        }
+        .with_hash(xmss_params.hash());
 
         WOTSPlusParams {
             inner_params,
@@ -41,30 +46,40 @@ impl WOTSPlusParams {
         &self.inner_params
     }
 
-    pub fn generate_keypair(&self, seed: &[u8], address: &[u8]) -> WOTSPlusKeypair {
+    /// Derive the WOTS+ keypair at `address` from the secret and public seeds
+    /// The public seed and address key the chain bitmasks, so the same secret
+    /// seed gives unrelated public keys under different public seeds.
+    pub fn generate_keypair(
+        &self,
+        seed: &[u8],
+        public_seed: &[u8],
+        address: &[u8],
+    ) -> WOTSPlusKeypair {
         // Always generate deterministic keypair with the inner params
-        let keypair = Self::generate_deterministic_keypair(&self.inner_params, seed, address);
+        let keypair =
+            Self::generate_deterministic_keypair(&self.inner_params, seed, public_seed, address);
 
-        if self.use_hypercube {
-            let tsl = TSL::new(TSLConfig::new(self.security_bits));
-            WOTSPlusKeypair {
-                keypair,
-                scheme: HypercubeScheme::TSL(tsl),
-            }
+        let scheme = if self.use_hypercube {
+            HypercubeScheme::TSL(TSL::new(TSLConfig::new(self.security_bits)))
         } else {
-            WOTSPlusKeypair {
-                keypair,
-                scheme: HypercubeScheme::None,
-            }
+            HypercubeScheme::None
+        };
+
+        WOTSPlusKeypair {
+            keypair,
+            scheme,
+            public_seed: public_seed.to_vec(),
+            address: address.to_vec(),
         }
     }
 
     fn generate_deterministic_keypair(
         params: &WotsParams,
         seed: &[u8],
+        public_seed: &[u8],
         address: &[u8],
     ) -> WotsKeypair {
-        let hasher = SHA256::new();
+        let hasher = params.hash();
         let mut sk_chains = Vec::with_capacity(params.chains());
         let mut pk_chains = Vec::with_capacity(params.chains());
 
@@ -79,8 +94,8 @@ impl WOTSPlusParams {
             // Generate secret key for this chain
             let sk_i = hasher.hash(&prf_input);
 
-            // Compute public key as H^{w-1}(sk_i)
-            let pk_i = crate::wots::hash_chain(&hasher, &sk_i, params.w() - 1);
+            // Compute public key as c^{w-1}(sk_i)
+            let pk_i = chain(&hasher, public_seed, address, i, &sk_i, 0, params.w() - 1);
 
             sk_chains.push(sk_i);
             pk_chains.push(pk_i);
@@ -97,6 +112,8 @@ impl WOTSPlusParams {
 pub struct WOTSPlusKeypair {
     keypair: WotsKeypair,
     scheme: HypercubeScheme,
+    public_seed: Vec<u8>,
+    address: Vec<u8>,
 }
 
 enum HypercubeScheme {
@@ -107,30 +124,196 @@ enum HypercubeScheme {
 }
 
 impl WOTSPlusKeypair {
+    /// σᵢ = c^{xᵢ}(skᵢ) for the digits xᵢ of the encoded message digest
+    /// Each chain runs all w-1 steps and keeps the xᵢ-th value through a
+    /// constant-time select, as `hash_chain_fixed` does for plain WOTS.
     pub fn sign(&self, message_digest: &[u8]) -> WotsSignature {
+        let params = self.keypair.public_key().params();
+        let digits = self
+            .message_digits(message_digest)
+            .unwrap_or_else(|e| panic!("message digest cannot be encoded: {:?}", e));
+        if let Err(e) = crate::wots::check_digest(&digits, params) {
+            panic!("{}", e);
+        }
+
+        let hasher = params.hash();
+        let sig_chains = digits
+            .iter()
+            .zip(self.keypair.secret_key().chains())
+            .enumerate()
+            .map(|(i, (&x_i, sk_i))| {
+                chain_fixed(
+                    &hasher,
+                    &self.public_seed,
+                    &self.address,
+                    i,
+                    sk_i,
+                    x_i,
+                    params.w() - 1,
+                )
+            })
+            .collect();
+
+        WotsSignature::from_chains(sig_chains)
+    }
+
+    /// WOTS digits in [0, w-1] signed for a message digest
+    fn message_digits(&self, message_digest: &[u8]) -> Result<Vec<usize>, MappingError> {
+        // Hypercube encodings use zero randomness, as in verification
+        let randomness = [0u8; 32];
+
         match &self.scheme {
             HypercubeScheme::None => {
                 // Convert hash to base-w representation
                 let w = self.keypair.public_key().params().w();
                 let chains = self.keypair.public_key().params().chains();
-                let digest_values = base_w_from_bytes(message_digest, w, chains);
-                self.keypair.sign_raw(&digest_values)
+                Ok(base_w_from_bytes(message_digest, w, chains))
             }
-            HypercubeScheme::TSL(tsl) => self.keypair.sign(message_digest, tsl),
-            HypercubeScheme::TL1C(tl1c) => self.keypair.sign(message_digest, tl1c),
-            HypercubeScheme::TLFC(tlfc) => self.keypair.sign(message_digest, tlfc),
+            HypercubeScheme::TSL(tsl) => tsl.encode_to_digest(message_digest, &randomness),
+            HypercubeScheme::TL1C(tl1c) => tl1c.encode_to_digest(message_digest, &randomness),
+            HypercubeScheme::TLFC(tlfc) => tlfc.encode_to_digest(message_digest, &randomness),
         }
     }
 
+    /// Public key chains pkᵢ = c^{w-1}(skᵢ) along the bitmasked WOTS+ chains
+    /// Crate-internal: `WotsPublicKey::verify` recomputes plain hash chains and
+    /// rejects every WOTS+ signature. Verify through `public_key_from_signature`
+    /// and compare `public_key_hash` instead.
+    pub(crate) fn public_key(&self) -> &crate::wots::WotsPublicKey {
+        self.keypair.public_key()
+    }
+
     pub fn public_key_hash(&self) -> Vec<u8> {
-        let hasher = SHA256::new();
-        let mut data = Vec::new();
+        let public_key = self.public_key();
+        crate::xmss::core::hash_wots_public_key(public_key.chains(), &public_key.params().hash())
+    }
+}
+
+/// WOTS+ public key chains recovered from a signature on `digits`
+/// pkᵢ = c^{w-1-xᵢ}(σᵢ) continuing chain i from step xᵢ. Returns `None`
+/// unless there is one digit in [0, w-1] and one hash-sized chain per index.
+pub(crate) fn public_key_from_signature(
+    hasher: &dyn HashFunction,
+    public_seed: &[u8],
+    address: &[u8],
+    digits: &[usize],
+    signature: &WotsSignature,
+    w: usize,
+) -> Option<Vec<Vec<u8>>> {
+    let chains = signature.chains();
+    if digits.len() != chains.len()
+        || digits.iter().any(|&x_i| x_i >= w)
+        || chains.iter().any(|c| c.len() != hasher.output_size())
+    {
+        return None;
+    }
+
+    let pk_chains = chains
+        .iter()
+        .zip(digits)
+        .enumerate()
+        .map(|(i, (sigma_i, &x_i))| {
+            chain(hasher, public_seed, address, i, sigma_i, x_i, w - 1 - x_i)
+        })
+        .collect();
+
+    Some(pk_chains)
+}
+
+/// WOTS+ chaining function: `steps` steps of chain `chain_index` from step `start`
+/// Step j maps x to H(pub_seed || addr || i || j || (x ⊕ maskⱼ)), where
+/// maskⱼ = H(pub_seed || addr || i || j) in its own hash domain. Keying every
+/// step by the public seed and address gives WOTS+ its tight reduction to
+/// second-preimage resistance. `input` must be one hash output long.
+pub(crate) fn chain(
+    hasher: &dyn HashFunction,
+    public_seed: &[u8],
+    address: &[u8],
+    chain_index: usize,
+    input: &[u8],
+    start: usize,
+    steps: usize,
+) -> Vec<u8> {
+    let mut key = ChainKey::new(hasher, public_seed, address, chain_index);
+    let mut value = input.to_vec();
+    for j in start..start + steps {
+        value = key.step(hasher, j, &value);
+    }
+    value
+}
+
+/// c^k(x) from x in time independent of k, running all `max_steps` steps
+fn chain_fixed(
+    hasher: &dyn HashFunction,
+    public_seed: &[u8],
+    address: &[u8],
+    chain_index: usize,
+    input: &[u8],
+    steps: usize,
+    max_steps: usize,
+) -> Vec<u8> {
+    assert!(steps <= max_steps, "steps must not exceed max_steps");
 
-        for chain in self.keypair.public_key().chains() {
-            data.extend_from_slice(chain);
+    let target = steps as u64;
+    let mut key = ChainKey::new(hasher, public_seed, address, chain_index);
+    let mut result = input.to_vec();
+    let mut current = input.to_vec();
+
+    for j in 0..max_steps {
+        current = key.step(hasher, j, &current);
+        let keep = ((j + 1) as u64).ct_eq(&target);
+        for (r, c) in result.iter_mut().zip(current.iter()) {
+            r.conditional_assign(c, keep);
         }
+    }
 
-        hasher.hash(&data)
+    result
+}
+
+/// Hash input shared by every step of one chain, built once per chain
+/// Laid out as tag || pub_seed || addr || i || j || value, so a step only
+/// rewrites the tag, the step counter and the masked value before hashing.
+struct ChainKey {
+    input: Vec<u8>,
+    /// Length of tag || pub_seed || addr || i || j
+    prefix_len: usize,
+}
+
+impl ChainKey {
+    fn new(
+        hasher: &dyn HashFunction,
+        public_seed: &[u8],
+        address: &[u8],
+        chain_index: usize,
+    ) -> Self {
+        let prefix_len = 1 + public_seed.len() + address.len() + 8;
+        let mut input = Vec::with_capacity(prefix_len + hasher.output_size());
+        input.push(HashDomain::ChainMask as u8);
+        input.extend_from_slice(public_seed);
+        input.extend_from_slice(address);
+        input.extend_from_slice(&(chain_index as u32).to_be_bytes());
+        input.extend_from_slice(&[0u8; 4]);
+        ChainKey { input, prefix_len }
+    }
+
+    /// Step `step` of the chain: H(key || (value ⊕ mask))
+    fn step(&mut self, hasher: &dyn HashFunction, step: usize, value: &[u8]) -> Vec<u8> {
+        assert_eq!(
+            value.len(),
+            hasher.output_size(),
+            "chain value must be one hash output long"
+        );
+
+        self.input.truncate(self.prefix_len);
+        self.input[self.prefix_len - 4..].copy_from_slice(&(step as u32).to_be_bytes());
+
+        self.input[0] = HashDomain::ChainMask as u8;
+        let mask = hasher.hash(&self.input);
+
+        self.input[0] = HashDomain::ChainStep as u8;
+        self.input
+            .extend(value.iter().zip(&mask).map(|(x, m)| x ^ m));
+        hasher.hash(&self.input)
     }
 }
 
@@ -172,3 +355,122 @@ fn base_w_from_bytes(bytes: &[u8], w: usize, out_len: usize) -> Vec<usize> {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(public_seed: &[u8]) -> (WOTSPlusParams, WOTSPlusKeypair) {
+        let params = WOTSPlusParams::from_xmss_params(&XMSSParams::new(2, 16, 16));
+        let keypair = params.generate_keypair(&[1u8; 32], public_seed, &3u32.to_be_bytes());
+        (params, keypair)
+    }
+
+    #[test]
+    fn test_wots_plus_sign_verify_round_trip() {
+        let public_seed = [2u8; 32];
+        let (params, keypair) = keypair(&public_seed);
+        let inner = params.inner_params();
+        let message_digest = inner.hash().hash(b"message");
+
+        let signature = keypair.sign(&message_digest);
+        let digits = keypair.message_digits(&message_digest).unwrap();
+        let recovered = public_key_from_signature(
+            &inner.hash(),
+            &public_seed,
+            &3u32.to_be_bytes(),
+            &digits,
+            &signature,
+            inner.w(),
+        )
+        .unwrap();
+        assert_eq!(recovered, keypair.public_key().chains());
+
+        // The wrong address does not recover the key
+        let wrong_address = public_key_from_signature(
+            &inner.hash(),
+            &public_seed,
+            &4u32.to_be_bytes(),
+            &digits,
+            &signature,
+            inner.w(),
+        )
+        .unwrap();
+        assert_ne!(wrong_address, keypair.public_key().chains());
+
+        // A digit outside [0, w-1] is rejected
+        let mut bad_digits = digits.clone();
+        bad_digits[0] = inner.w();
+        assert!(public_key_from_signature(
+            &inner.hash(),
+            &public_seed,
+            &3u32.to_be_bytes(),
+            &bad_digits,
+            &signature,
+            inner.w(),
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_wots_plus_public_seed_changes_public_key() {
+        let (_, a) = keypair(&[2u8; 32]);
+        let (_, b) = keypair(&[3u8; 32]);
+        assert_ne!(a.public_key().chains(), b.public_key().chains());
+        assert_ne!(a.public_key_hash(), b.public_key_hash());
+    }
+
+    #[test]
+    fn test_wots_plus_chain_differs_from_plain_hash_chain() {
+        let hasher = crate::crypto::hash::SHA256::new();
+        let input = hasher.hash(b"chain input");
+
+        let masked = chain(&hasher, &[0u8; 32], &[0u8; 4], 0, &input, 0, 3);
+        assert_ne!(masked, crate::wots::hash_chain(&hasher, &input, 3));
+
+        // Chains compose: c^{3}(x) continues c^{1}(x) from step 1
+        let first = chain(&hasher, &[0u8; 32], &[0u8; 4], 0, &input, 0, 1);
+        assert_eq!(
+            chain(&hasher, &[0u8; 32], &[0u8; 4], 0, &first, 1, 2),
+            masked
+        );
+
+        for k in 0..=3 {
+            assert_eq!(
+                chain_fixed(&hasher, &[0u8; 32], &[0u8; 4], 0, &input, k, 3),
+                chain(&hasher, &[0u8; 32], &[0u8; 4], 0, &input, 0, k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_wots_plus_chain_step_matches_domain_hashes() {
+        let hasher = crate::crypto::hash::SHA256::new();
+        let (seed, address) = ([5u8; 32], [1, 2, 3, 4]);
+        let input = hasher.hash(b"chain input");
+
+        // Step 2 of chain 7, spelled out with the incremental domain hashers
+        let keyed = |domain: HashDomain| {
+            let mut state = domain.hasher(&hasher);
+            state.update(&seed);
+            state.update(&address);
+            state.update(&7u32.to_be_bytes());
+            state.update(&2u32.to_be_bytes());
+            state
+        };
+        let mask = keyed(HashDomain::ChainMask).finalize();
+        let mut state = keyed(HashDomain::ChainStep);
+        state.update(
+            &input
+                .iter()
+                .zip(&mask)
+                .map(|(x, m)| x ^ m)
+                .collect::<Vec<u8>>(),
+        );
+
+        assert_eq!(
+            chain(&hasher, &seed, &address, 7, &input, 2, 1),
+            state.finalize()
+        );
+    }
+}